@@ -0,0 +1,29 @@
+// SPDX-License-Identifier: MPL-2.0
+use miette::{IntoDiagnostic, Result, WrapErr};
+use mpris2_zbus::{compliance, media_player::MediaPlayer};
+use zbus::Connection;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+	let connection = Connection::session()
+		.await
+		.into_diagnostic()
+		.wrap_err("Failed to establish session D-Bus connection")?;
+	let media_players = MediaPlayer::new_all(&connection)
+		.await
+		.into_diagnostic()
+		.wrap_err("Failed get available players")?;
+	let mut compliant = true;
+	for media_player in media_players {
+		let report = compliance::check(&media_player)
+			.await
+			.into_diagnostic()
+			.wrap_err_with(|| format!("Failed to check media player '{}'", media_player))?;
+		compliant &= report.is_compliant();
+		println!("{}", report);
+	}
+	if !compliant {
+		std::process::exit(1);
+	}
+	Ok(())
+}
@@ -85,7 +85,7 @@ async fn main() -> Result<()> {
 			.wrap_err_with(|| format!("Failed to get track list for media player '{}'", name))?;
 		if let Some(track_list) = track_list {
 			let tracks = track_list
-				.detailed_tracks()
+				.tracks_with_metadata()
 				.await
 				.into_diagnostic()
 				.wrap_err_with(|| {
@@ -106,7 +106,7 @@ async fn main() -> Result<()> {
 				if let Some(composers) = metadata.composer() {
 					println!("\t\t\tComposers: {}", composers.join(", "));
 				}
-				if let Some(bpm) = metadata.bpm() {
+				if let Some(bpm) = metadata.audio_bpm() {
 					println!("\t\t\tBPM: {}", bpm);
 				}
 			}
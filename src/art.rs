@@ -0,0 +1,192 @@
+// SPDX-License-Identifier: MPL-2.0
+pub mod cache;
+
+use crate::{
+	error::{Error, Result},
+	metadata::{file_url_to_path, Metadata},
+	playlists::playlist::Playlist,
+};
+use base64::Engine;
+use std::{
+	io::{self, Read},
+	path::{Path, PathBuf},
+};
+
+/// The largest image that will be downloaded, to protect against misbehaving servers.
+/// Larger images fail to load, rather than being cut off.
+const MAX_DOWNLOAD_SIZE: u64 = 32 * 1024 * 1024;
+
+/// An image fetched from an art URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Art {
+	/// The raw image data.
+	pub data: Vec<u8>,
+	/// The MIME type of the image, detected from its contents where possible.
+	pub mime_type: String,
+}
+
+impl Metadata {
+	/// Fetches the image referenced by `mpris:artUrl`.
+	///
+	/// `file://` paths are read from disk, `data:` URIs are decoded and `http(s)://` URLs are downloaded.
+	/// Returns None if the metadata has no art URL.
+	pub async fn fetch_art(&self) -> Result<Option<Art>> {
+		match self.art_url() {
+			Some(url) => fetch(&url).await.map(Some),
+			None => Ok(None),
+		}
+	}
+}
+
+/// A resolved playlist icon.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlaylistIcon {
+	/// An image read from disk, decoded or downloaded.
+	Image(Art),
+	/// The name of an icon in the desktop's icon theme, to be looked up by the UI.
+	Themed(String),
+}
+
+impl Playlist {
+	/// Resolves the playlist's icon.
+	///
+	/// URLs are fetched like [Metadata::fetch_art] does and absolute paths are read from disk;
+	/// anything else is taken to be a theme icon name. Returns None if the playlist has no icon.
+	pub async fn fetch_icon(&self) -> Result<Option<PlaylistIcon>> {
+		let icon = self.icon().trim();
+		if icon.is_empty() {
+			Ok(None)
+		} else if icon.starts_with('/') {
+			read_file(PathBuf::from(icon))
+				.await
+				.map(|art| Some(PlaylistIcon::Image(art)))
+		} else if icon.contains(':') {
+			fetch(icon).await.map(|art| Some(PlaylistIcon::Image(art)))
+		} else {
+			Ok(Some(PlaylistIcon::Themed(icon.to_string())))
+		}
+	}
+}
+
+/// Fetches the image at `url`, which may be a `file://`, `data:` or `http(s)://` URL.
+pub async fn fetch(url: &str) -> Result<Art> {
+	let scheme = url
+		.split_once(':')
+		.map(|(scheme, _)| scheme.to_ascii_lowercase())
+		.ok_or_else(|| Error::InvalidUrl(url.to_string()))?;
+	match scheme.as_str() {
+		"file" => {
+			let path = file_url_to_path(url).ok_or_else(|| Error::InvalidUrl(url.to_string()))?;
+			read_file(path).await
+		}
+		"data" => decode_data_uri(url),
+		"http" | "https" => download(url.to_string()).await,
+		_ => Err(Error::InvalidUrl(url.to_string())),
+	}
+}
+
+pub(crate) async fn read_file(path: PathBuf) -> Result<Art> {
+	let (path, data) = blocking::unblock(move || std::fs::read(&path).map(|data| (path, data)))
+		.await
+		.map_err(Error::from)?;
+	let mime_type = sniff_mime_type(&data)
+		.or_else(|| mime_type_from_extension(&path))
+		.unwrap_or("application/octet-stream");
+	Ok(Art {
+		data,
+		mime_type: mime_type.to_string(),
+	})
+}
+
+async fn download(url: String) -> Result<Art> {
+	blocking::unblock(move || {
+		let response = ureq::get(&url)
+			.call()
+			.map_err(|err| Error::Http(Box::new(err)))?;
+		let header = response.header("Content-Type").map(str::to_string);
+		let mut data = Vec::new();
+		response
+			.into_reader()
+			.take(MAX_DOWNLOAD_SIZE + 1)
+			.read_to_end(&mut data)?;
+		if data.len() as u64 > MAX_DOWNLOAD_SIZE {
+			return Err(Error::Io(io::Error::new(
+				io::ErrorKind::InvalidData,
+				format!("{} is larger than {} bytes", url, MAX_DOWNLOAD_SIZE),
+			)));
+		}
+		let mime_type = sniff_mime_type(&data)
+			.map(str::to_string)
+			.or(header)
+			.unwrap_or_else(|| "application/octet-stream".to_string());
+		Ok(Art { data, mime_type })
+	})
+	.await
+}
+
+/// Decodes a `data:[<mime type>][;base64],<data>` URI.
+fn decode_data_uri(url: &str) -> Result<Art> {
+	let invalid = || Error::InvalidUrl(url.to_string());
+	let (header, payload) = url["data:".len()..].split_once(',').ok_or_else(invalid)?;
+	let (header, is_base64) = match header.strip_suffix(";base64") {
+		Some(header) => (header, true),
+		None => (header, false),
+	};
+	let data = if is_base64 {
+		let payload = percent_encoding::percent_decode_str(payload).collect::<Vec<u8>>();
+		base64::engine::general_purpose::STANDARD
+			.decode(payload)
+			.map_err(|_| invalid())?
+	} else {
+		percent_encoding::percent_decode_str(payload).collect()
+	};
+	let declared = header.split(';').next().filter(|mime| !mime.is_empty());
+	let mime_type = sniff_mime_type(&data)
+		.or(declared)
+		.unwrap_or("application/octet-stream");
+	Ok(Art {
+		mime_type: mime_type.to_string(),
+		data,
+	})
+}
+
+/// A stable hash for file names; std's hashers aren't guaranteed to be stable across releases.
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+	bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+		(hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+	})
+}
+
+pub(crate) fn sniff_mime_type(data: &[u8]) -> Option<&'static str> {
+	if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+		Some("image/png")
+	} else if data.starts_with(&[0xff, 0xd8, 0xff]) {
+		Some("image/jpeg")
+	} else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+		Some("image/gif")
+	} else if data.len() >= 12 && &data[..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+		Some("image/webp")
+	} else if data.starts_with(b"BM") {
+		Some("image/bmp")
+	} else if data.starts_with(&[0, 0, 1, 0]) {
+		Some("image/x-icon")
+	} else if data.starts_with(b"<svg") || data.starts_with(b"<?xml") {
+		Some("image/svg+xml")
+	} else {
+		None
+	}
+}
+
+fn mime_type_from_extension(path: &Path) -> Option<&'static str> {
+	let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+	match extension.as_str() {
+		"png" => Some("image/png"),
+		"jpg" | "jpeg" => Some("image/jpeg"),
+		"gif" => Some("image/gif"),
+		"webp" => Some("image/webp"),
+		"bmp" => Some("image/bmp"),
+		"ico" => Some("image/x-icon"),
+		"svg" => Some("image/svg+xml"),
+		_ => None,
+	}
+}
@@ -0,0 +1,155 @@
+// SPDX-License-Identifier: MPL-2.0
+use super::{fetch, fnv1a, Art};
+use crate::{
+	error::{Error, Result},
+	metadata::Metadata,
+};
+use std::{
+	fs::{self, File},
+	path::{Path, PathBuf},
+	time::SystemTime,
+};
+
+/// The default size limit of the cache, in bytes.
+pub const DEFAULT_MAX_SIZE: u64 = 64 * 1024 * 1024;
+
+/// A disk cache for fetched art, stored under `$XDG_CACHE_HOME/mpris2-zbus/art`.
+///
+/// Entries are keyed by an arbitrary string, usually the art URL or a track id.
+/// Once the cache grows beyond its size limit, the least recently used entries are evicted.
+#[derive(Debug, Clone)]
+pub struct ArtCache {
+	dir: PathBuf,
+	max_size: u64,
+}
+
+impl ArtCache {
+	/// Creates a cache in the default XDG cache location.
+	pub fn new() -> Result<Self> {
+		let cache_home = std::env::var_os("XDG_CACHE_HOME")
+			.map(PathBuf::from)
+			.filter(|path| path.is_absolute())
+			.or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+			.ok_or_else(|| {
+				Error::Io(std::io::Error::new(
+					std::io::ErrorKind::NotFound,
+					"neither XDG_CACHE_HOME nor HOME is set",
+				))
+			})?;
+		Ok(Self::with_dir(cache_home.join("mpris2-zbus").join("art")))
+	}
+
+	/// Creates a cache stored in `dir`.
+	pub fn with_dir<P: Into<PathBuf>>(dir: P) -> Self {
+		Self {
+			dir: dir.into(),
+			max_size: DEFAULT_MAX_SIZE,
+		}
+	}
+
+	/// Sets the size limit of the cache, in bytes.
+	pub fn max_size(mut self, max_size: u64) -> Self {
+		self.max_size = max_size;
+		self
+	}
+
+	/// The directory the cache is stored in.
+	pub fn dir(&self) -> &Path {
+		&self.dir
+	}
+
+	/// Returns the cached art for `key`, if any.
+	pub async fn get(&self, key: &str) -> Option<Art> {
+		let path = self.path(key);
+		blocking::unblock(move || read_entry(&path)).await
+	}
+
+	/// Stores `art` under `key`, evicting old entries if the cache grows too large.
+	pub async fn insert(&self, key: &str, art: &Art) -> Result<()> {
+		let cache = self.clone();
+		let path = self.path(key);
+		let mut contents = Vec::with_capacity(art.mime_type.len() + 1 + art.data.len());
+		contents.extend_from_slice(art.mime_type.as_bytes());
+		contents.push(b'\n');
+		contents.extend_from_slice(&art.data);
+		blocking::unblock(move || {
+			fs::create_dir_all(&cache.dir)?;
+			fs::write(&path, contents)?;
+			cache.evict()
+		})
+		.await
+	}
+
+	/// Returns the art at `url`, fetching and caching it if it isn't cached yet.
+	pub async fn fetch(&self, url: &str) -> Result<Art> {
+		if let Some(art) = self.get(url).await {
+			return Ok(art);
+		}
+		let art = fetch(url).await?;
+		self.insert(url, &art).await?;
+		Ok(art)
+	}
+
+	/// Returns the art referenced by `mpris:artUrl`, fetching and caching it if needed.
+	///
+	/// Returns None if the metadata has no art URL.
+	pub async fn fetch_for(&self, metadata: &Metadata) -> Result<Option<Art>> {
+		match metadata.art_url() {
+			Some(url) => self.fetch(&url).await.map(Some),
+			None => Ok(None),
+		}
+	}
+
+	/// Removes every cached entry.
+	pub async fn clear(&self) -> Result<()> {
+		let dir = self.dir.clone();
+		blocking::unblock(move || match fs::remove_dir_all(&dir) {
+			Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(Error::from(err)),
+			_ => Ok(()),
+		})
+		.await
+	}
+
+	fn path(&self, key: &str) -> PathBuf {
+		self.dir.join(format!("{:016x}", fnv1a(key.as_bytes())))
+	}
+
+	/// Deletes the least recently used entries until the cache fits within its size limit.
+	fn evict(&self) -> Result<()> {
+		let mut entries = Vec::new();
+		let mut total = 0;
+		for entry in fs::read_dir(&self.dir)? {
+			let entry = entry?;
+			let metadata = match entry.metadata() {
+				Ok(metadata) if metadata.is_file() => metadata,
+				_ => continue,
+			};
+			total += metadata.len();
+			let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+			entries.push((modified, metadata.len(), entry.path()));
+		}
+		entries.sort();
+		for (_, size, path) in entries {
+			if total <= self.max_size {
+				break;
+			}
+			fs::remove_file(path)?;
+			total -= size;
+		}
+		Ok(())
+	}
+}
+
+fn read_entry(path: &Path) -> Option<Art> {
+	let contents = fs::read(path).ok()?;
+	// Mark the entry as recently used, so it's evicted last.
+	if let Ok(file) = File::options().write(true).open(path) {
+		let _ = file.set_modified(SystemTime::now());
+	}
+	let split = contents.iter().position(|b| *b == b'\n')?;
+	let mime_type = String::from_utf8(contents[..split].to_vec()).ok()?;
+	Some(Art {
+		data: contents[split + 1..].to_vec(),
+		mime_type,
+	})
+}
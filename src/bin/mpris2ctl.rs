@@ -0,0 +1,228 @@
+// SPDX-License-Identifier: MPL-2.0
+use futures_util::StreamExt;
+use mpris2_zbus::{
+	error::{Error, Result},
+	media_player::{selector::PlayerSelector, MediaPlayer},
+	metadata::Metadata,
+	player::Player,
+};
+use std::{env, process::ExitCode};
+use time::Duration;
+use zbus::Connection;
+
+const USAGE: &str = "\
+Usage: mpris2ctl [--player NAME] COMMAND
+
+Commands:
+  list                      List the available players
+  status                    Show the playback status, track, position and volume
+  play-pause                Toggle between playing and paused
+  next                      Skip to the next track
+  seek [+|-]SECONDS         Seek relative to the position, or to an absolute position
+  volume [[+|-]VOLUME]      Show the volume, or set it, e.g. 0.5 or +0.1
+  metadata [--format FMT]   Show the metadata, or render it, e.g. \"{artist} - {title}\"
+  follow [--format FMT]     Render the metadata every time the track changes
+
+--player matches a player by bus name, application name, identity or
+desktop entry. Otherwise, playing players are preferred over paused ones.";
+
+const DEFAULT_FORMAT: &str = "{artist} - {title}";
+
+fn main() -> ExitCode {
+	let mut args = env::args().skip(1).collect::<Vec<_>>();
+	let player = match args.iter().position(|arg| arg == "--player" || arg == "-p") {
+		Some(index) if index + 1 < args.len() => {
+			let name = args.remove(index + 1);
+			args.remove(index);
+			Some(name)
+		}
+		Some(_) => return usage(),
+		None => None,
+	};
+	let Some(command) = args.first().cloned() else {
+		return usage();
+	};
+	let result = async_io::block_on(run(&command, &args[1..], player.as_deref()));
+	match result {
+		Ok(true) => ExitCode::SUCCESS,
+		Ok(false) => usage(),
+		Err(err) => {
+			eprintln!("mpris2ctl: {}", err);
+			ExitCode::FAILURE
+		}
+	}
+}
+
+fn usage() -> ExitCode {
+	eprintln!("{}", USAGE);
+	ExitCode::from(2)
+}
+
+/// Runs a command, returning false if its arguments are invalid.
+async fn run(command: &str, args: &[String], player: Option<&str>) -> Result<bool> {
+	let connection = Connection::session().await?;
+	if command == "list" {
+		return list(&connection).await.map(|_| true);
+	}
+	let Some(media_player) = select(&connection, player).await? else {
+		return Err(Error::Fdo(zbus::fdo::Error::ServiceUnknown(
+			"No player found".to_string(),
+		)));
+	};
+	let player = media_player.player().await?;
+	match (command, args) {
+		("status", []) => status(&media_player, &player).await?,
+		("play-pause", []) => player.play_pause().await?,
+		("next", []) => player.next().await?,
+		("seek", [offset]) => match parse_seconds(offset) {
+			Some((true, offset)) => {
+				if !player.seek(offset).await? {
+					return Err(failed("The player can't seek"));
+				}
+			}
+			Some((false, position)) => {
+				if !player.can_seek().await? {
+					return Err(failed("The player can't seek"));
+				}
+				let Some(track) = player.metadata().await?.track() else {
+					return Err(failed("No track is playing"));
+				};
+				player.set_position(&track, position).await?;
+			}
+			None => return Ok(false),
+		},
+		("volume", []) => println!("{:.2}", player.volume().await?),
+		("volume", [volume]) => {
+			let relative = volume.starts_with(['+', '-']);
+			let Ok(value) = volume.parse::<f64>() else {
+				return Ok(false);
+			};
+			let volume = match relative {
+				true => player.volume().await? + value,
+				false => value,
+			};
+			player.set_volume(volume.max(0.0)).await?;
+		}
+		("metadata", []) => {
+			let metadata = player.metadata().await?;
+			let mut keys = metadata.keys().collect::<Vec<_>>();
+			keys.sort_unstable();
+			for key in keys {
+				if let Some(value) = metadata.as_map().get(key) {
+					println!("{:<24}{}", key, value);
+				}
+			}
+		}
+		("metadata", [flag, format]) if flag == "--format" => {
+			println!("{}", player.metadata().await?.format(format));
+		}
+		("follow", []) => follow(&player, DEFAULT_FORMAT).await?,
+		("follow", [flag, format]) if flag == "--format" => follow(&player, format).await?,
+		_ => return Ok(false),
+	}
+	Ok(true)
+}
+
+async fn list(connection: &Connection) -> Result<()> {
+	for media_player in MediaPlayer::new_all_lossy(connection).await?.0 {
+		let status = match media_player.player().await {
+			Ok(player) => player
+				.playback_status()
+				.await
+				.map(|status| status.to_string()),
+			Err(err) => Err(err),
+		};
+		println!(
+			"{}\t{}\t{}",
+			media_player.destination(),
+			media_player.identity().await.unwrap_or_default(),
+			status.unwrap_or_else(|_| "Unknown".to_string())
+		);
+	}
+	Ok(())
+}
+
+/// Picks the player matching `name`, or the best player if no name is given.
+async fn select(connection: &Connection, name: Option<&str>) -> Result<Option<MediaPlayer>> {
+	let Some(name) = name else {
+		return PlayerSelector::new().select(connection).await;
+	};
+	let mut candidates = Vec::new();
+	for media_player in MediaPlayer::new_all_lossy(connection).await?.0 {
+		let application = media_player
+			.mpris_name()
+			.map(|mpris_name| mpris_name.application().to_string())
+			.unwrap_or_default();
+		let identity = media_player.identity().await.unwrap_or_default();
+		let desktop_entry = media_player.desktop_entry().await.unwrap_or_default();
+		let matches = [
+			media_player.destination().as_str(),
+			&application,
+			&identity,
+			&desktop_entry,
+		]
+		.iter()
+		.any(|candidate| candidate.eq_ignore_ascii_case(name));
+		if matches {
+			candidates.push(media_player);
+		}
+	}
+	Ok(PlayerSelector::new().select_from(candidates).await)
+}
+
+async fn status(media_player: &MediaPlayer, player: &Player) -> Result<()> {
+	let (metadata, position) = player.metadata_with_position().await?;
+	println!(
+		"{} ({})",
+		player.playback_status().await?,
+		media_player.identity().await?
+	);
+	println!("{}", metadata.format(DEFAULT_FORMAT));
+	if let Some(position) = position {
+		match metadata.length() {
+			Some(length) => println!("{} / {}", clock(position), clock(length)),
+			None => println!("{}", clock(position)),
+		}
+	}
+	if let Ok(volume) = player.volume().await {
+		println!("volume {:.2}", volume);
+	}
+	Ok(())
+}
+
+/// Prints the current metadata, then the metadata whenever it changes,
+/// skipping updates which fail to be read.
+async fn follow(player: &Player, format: &str) -> Result<()> {
+	let mut changes = player.receive_metadata_changed().await;
+	let mut last = player.metadata().await?.format(format);
+	println!("{}", last);
+	while let Some(change) = changes.next().await {
+		let Ok(metadata) = change.get().await else {
+			continue;
+		};
+		let line = Metadata::from(metadata).format(format);
+		if line != last {
+			println!("{}", line);
+			last = line;
+		}
+	}
+	Ok(())
+}
+
+/// Parses `+5`, `-2.5` or `30` into whether it is relative, and the duration.
+///
+/// Returns None for values which aren't numbers, or too large for a duration.
+fn parse_seconds(arg: &str) -> Option<(bool, Duration)> {
+	let seconds = Duration::checked_seconds_f64(arg.parse::<f64>().ok()?)?;
+	Some((arg.starts_with(['+', '-']), seconds))
+}
+
+fn failed(message: &str) -> Error {
+	Error::Fdo(zbus::fdo::Error::Failed(message.to_string()))
+}
+
+/// Formats a position as `m:ss`.
+fn clock(duration: Duration) -> String {
+	let seconds = duration.whole_seconds().max(0);
+	format!("{}:{:02}", seconds / 60, seconds % 60)
+}
@@ -1,5 +1,8 @@
 // SPDX-License-Identifier: MPL-2.0
 pub mod media_player;
+#[cfg(feature = "notify")]
+pub mod notifications;
 pub mod player;
+pub mod playerctld;
 pub mod playlist;
 pub mod track_list;
@@ -41,10 +41,20 @@ trait MediaPlayer2 {
 	#[dbus_proxy(property)]
 	fn can_raise(&self) -> zbus::Result<bool>;
 
+	/// CanSetFullscreen property (optional)
+	#[dbus_proxy(property)]
+	fn can_set_fullscreen(&self) -> zbus::Result<bool>;
+
 	/// DesktopEntry property
 	#[dbus_proxy(property)]
 	fn desktop_entry(&self) -> zbus::Result<String>;
 
+	/// Fullscreen property (optional)
+	#[dbus_proxy(property)]
+	fn fullscreen(&self) -> zbus::Result<bool>;
+	#[dbus_proxy(property)]
+	fn set_fullscreen(&self, value: bool) -> zbus::Result<()>;
+
 	/// HasTrackList property
 	#[dbus_proxy(property)]
 	fn has_track_list(&self) -> zbus::Result<bool>;
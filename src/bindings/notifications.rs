@@ -0,0 +1,30 @@
+// SPDX-License-Identifier: MPL-2.0
+//! # DBus interface proxy for: `org.freedesktop.Notifications`
+//!
+//! Only the subset of the
+//! [Desktop Notifications Specification](https://specifications.freedesktop.org/notification-spec/latest/)
+//! needed to post now-playing notifications is bound here.
+
+use std::collections::HashMap;
+use zbus::{dbus_proxy, zvariant::Value};
+
+#[dbus_proxy(
+	interface = "org.freedesktop.Notifications",
+	default_service = "org.freedesktop.Notifications",
+	default_path = "/org/freedesktop/Notifications"
+)]
+trait Notifications {
+	/// Notify method
+	#[allow(clippy::too_many_arguments)]
+	fn notify(
+		&self,
+		app_name: &str,
+		replaces_id: u32,
+		app_icon: &str,
+		summary: &str,
+		body: &str,
+		actions: &[&str],
+		hints: HashMap<&str, Value<'_>>,
+		expire_timeout: i32,
+	) -> zbus::Result<u32>;
+}
@@ -0,0 +1,32 @@
+// SPDX-License-Identifier: MPL-2.0
+//! # DBus interface proxy for: `com.github.altdesktop.playerctld`
+//!
+//! The interface exported by [playerctld](https://github.com/altdesktop/playerctl)
+//! under the `org.mpris.MediaPlayer2.playerctld` bus name, next to its own MPRIS interfaces.
+
+use zbus::dbus_proxy;
+
+#[dbus_proxy(
+	interface = "com.github.altdesktop.playerctld",
+	default_service = "org.mpris.MediaPlayer2.playerctld",
+	default_path = "/org/mpris/MediaPlayer2"
+)]
+trait Playerctld {
+	/// Shift method
+	fn shift(&self) -> zbus::Result<String>;
+
+	/// Unshift method
+	fn unshift(&self) -> zbus::Result<String>;
+
+	/// ActivePlayerChangeBegin signal
+	#[dbus_proxy(signal)]
+	fn active_player_change_begin(&self, name: &str) -> zbus::Result<()>;
+
+	/// ActivePlayerChangeEnd signal
+	#[dbus_proxy(signal)]
+	fn active_player_change_end(&self, name: &str) -> zbus::Result<()>;
+
+	/// PlayerNames property
+	#[dbus_proxy(property)]
+	fn player_names(&self) -> zbus::Result<Vec<String>>;
+}
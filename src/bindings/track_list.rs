@@ -34,7 +34,7 @@ trait TrackList {
 	/// GetTracksMetadata method
 	fn get_tracks_metadata(
 		&self,
-		track_ids: Vec<TrackId>,
+		track_ids: &[TrackId],
 	) -> zbus::Result<Vec<std::collections::HashMap<String, zbus::zvariant::OwnedValue>>>;
 
 	/// GoTo method
@@ -43,11 +43,31 @@ trait TrackList {
 	/// RemoveTrack method
 	fn remove_track(&self, track_id: &TrackId) -> zbus::Result<()>;
 
+	/// TrackAdded signal
+	#[dbus_proxy(signal)]
+	fn track_added(
+		&self,
+		metadata: std::collections::HashMap<String, zbus::zvariant::OwnedValue>,
+		after_track: TrackId,
+	) -> zbus::Result<()>;
+
 	/// TrackListReplaced signal
 	#[dbus_proxy(signal)]
 	fn track_list_replaced(&self, tracks: Vec<TrackId>, current_track: TrackId)
 		-> zbus::Result<()>;
 
+	/// TrackMetadataChanged signal
+	#[dbus_proxy(signal)]
+	fn track_metadata_changed(
+		&self,
+		track_id: TrackId,
+		metadata: std::collections::HashMap<String, zbus::zvariant::OwnedValue>,
+	) -> zbus::Result<()>;
+
+	/// TrackRemoved signal
+	#[dbus_proxy(signal)]
+	fn track_removed(&self, track_id: TrackId) -> zbus::Result<()>;
+
 	/// CanEditTracks property
 	#[dbus_proxy(property)]
 	fn can_edit_tracks(&self) -> zbus::Result<bool>;
@@ -0,0 +1,497 @@
+// SPDX-License-Identifier: MPL-2.0
+use crate::{
+	error::Result,
+	media_player::{MediaPlayer, MediaPlayerInterfaces},
+	metadata::{validation::Violation, Metadata},
+	player::{LoopStatus, PlaybackStatus},
+};
+use async_io::Timer;
+use futures_util::{future, pin_mut, StreamExt};
+use std::{
+	collections::HashMap,
+	fmt::{self, Display},
+	str::FromStr,
+	time::Duration,
+};
+use zbus::{
+	fdo::PropertiesProxy,
+	names::InterfaceName,
+	zvariant::{OwnedValue, Value},
+};
+
+const ROOT: &str = "org.mpris.MediaPlayer2";
+const PLAYER: &str = "org.mpris.MediaPlayer2.Player";
+const TRACK_LIST: &str = "org.mpris.MediaPlayer2.TrackList";
+const PLAYLISTS: &str = "org.mpris.MediaPlayer2.Playlists";
+
+/// A property's name, signature, and whether the spec requires it.
+type Property = (&'static str, &'static str, bool);
+
+/// The properties of each interface.
+const PROPERTIES: &[(&str, &[Property])] = &[
+	(
+		ROOT,
+		&[
+			("CanQuit", "b", true),
+			("Fullscreen", "b", false),
+			("CanSetFullscreen", "b", false),
+			("CanRaise", "b", true),
+			("HasTrackList", "b", true),
+			("Identity", "s", true),
+			("DesktopEntry", "s", false),
+			("SupportedUriSchemes", "as", true),
+			("SupportedMimeTypes", "as", true),
+		],
+	),
+	(
+		PLAYER,
+		&[
+			("PlaybackStatus", "s", true),
+			("LoopStatus", "s", false),
+			("Rate", "d", true),
+			("Shuffle", "b", false),
+			("Metadata", "a{sv}", true),
+			("Volume", "d", true),
+			("Position", "x", true),
+			("MinimumRate", "d", true),
+			("MaximumRate", "d", true),
+			("CanGoNext", "b", true),
+			("CanGoPrevious", "b", true),
+			("CanPlay", "b", true),
+			("CanPause", "b", true),
+			("CanSeek", "b", true),
+			("CanControl", "b", true),
+		],
+	),
+	(
+		TRACK_LIST,
+		&[("Tracks", "ao", true), ("CanEditTracks", "b", true)],
+	),
+	(
+		PLAYLISTS,
+		&[
+			("PlaylistCount", "u", true),
+			("Orderings", "as", true),
+			("ActivePlaylist", "(b(oss))", true),
+		],
+	),
+];
+
+/// The Can* properties which must be false when `CanControl` is false.
+const CONTROLS: [&str; 5] = [
+	"CanGoNext",
+	"CanGoPrevious",
+	"CanPlay",
+	"CanPause",
+	"CanSeek",
+];
+
+/// How long [check] listens to the player's signals.
+const LISTEN_TIME: Duration = Duration::from_secs(1);
+
+/// How serious an [Issue] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+	/// The player deviates from a recommendation, or behaves oddly.
+	Warning,
+	/// The player breaks a requirement of the spec.
+	Error,
+}
+
+/// A way in which a player deviates from the MPRIS spec, as found by [check].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Issue {
+	/// The player doesn't implement a required interface.
+	MissingInterface(&'static str),
+	/// A required property is missing, or can't be read.
+	MissingProperty {
+		interface: &'static str,
+		property: &'static str,
+	},
+	/// A property has the wrong D-Bus type.
+	IncorrectType {
+		interface: &'static str,
+		property: &'static str,
+		expected: &'static str,
+		actual: String,
+	},
+	/// A property holds a value the spec doesn't allow.
+	InvalidValue {
+		interface: &'static str,
+		property: &'static str,
+		value: String,
+		reason: &'static str,
+	},
+	/// The current metadata deviates from the metadata guidelines.
+	InvalidMetadata(Violation),
+	/// A Can* property is true, although `CanControl` is false.
+	UncontrolledCapability(&'static str),
+	/// `Position` was announced by `PropertiesChanged`, instead of only by `Seeked`.
+	PositionChangedSignalled,
+	/// A `PropertiesChanged` signal carried a value of the wrong D-Bus type.
+	IncorrectSignalType {
+		interface: &'static str,
+		property: &'static str,
+		expected: &'static str,
+		actual: String,
+	},
+	/// The position didn't advance while the player was playing.
+	PositionNotAdvancing,
+}
+
+impl Issue {
+	pub fn severity(&self) -> Severity {
+		match self {
+			Self::PositionNotAdvancing => Severity::Warning,
+			Self::InvalidMetadata(Violation::InvalidDate { .. } | Violation::InvalidUrl { .. }) => {
+				Severity::Warning
+			}
+			_ => Severity::Error,
+		}
+	}
+}
+
+impl Display for Issue {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::MissingInterface(interface) => write!(f, "{} is not implemented", interface),
+			Self::MissingProperty {
+				interface,
+				property,
+			} => write!(f, "{}.{} is missing", interface, property),
+			Self::IncorrectType {
+				interface,
+				property,
+				expected,
+				actual,
+			} => write!(
+				f,
+				"{}.{} should have type {}, but has {}",
+				interface, property, expected, actual
+			),
+			Self::InvalidValue {
+				interface,
+				property,
+				value,
+				reason,
+			} => write!(f, "{}.{} is {}, but {}", interface, property, value, reason),
+			Self::InvalidMetadata(violation) => write!(f, "Metadata: {}", violation),
+			Self::UncontrolledCapability(property) => {
+				write!(f, "{} is true, although CanControl is false", property)
+			}
+			Self::PositionChangedSignalled => {
+				write!(f, "Position was announced by PropertiesChanged")
+			}
+			Self::IncorrectSignalType {
+				interface,
+				property,
+				expected,
+				actual,
+			} => write!(
+				f,
+				"PropertiesChanged sent {}.{} with type {}, instead of {}",
+				interface, property, actual, expected
+			),
+			Self::PositionNotAdvancing => write!(f, "Position didn't advance while playing"),
+		}
+	}
+}
+
+/// The result of a [check].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComplianceReport {
+	/// The bus name of the checked player.
+	pub player: String,
+	/// The interfaces the player implements.
+	pub interfaces: MediaPlayerInterfaces,
+	pub issues: Vec<Issue>,
+}
+
+impl ComplianceReport {
+	/// Whether the player meets every requirement, ignoring warnings.
+	pub fn is_compliant(&self) -> bool {
+		self.errors().next().is_none()
+	}
+
+	/// The issues which break requirements of the spec.
+	pub fn errors(&self) -> impl Iterator<Item = &Issue> {
+		self.issues
+			.iter()
+			.filter(|issue| issue.severity() == Severity::Error)
+	}
+
+	/// The issues which only deviate from recommendations.
+	pub fn warnings(&self) -> impl Iterator<Item = &Issue> {
+		self.issues
+			.iter()
+			.filter(|issue| issue.severity() == Severity::Warning)
+	}
+}
+
+impl Display for ComplianceReport {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if self.issues.is_empty() {
+			return write!(f, "{}: no issues found", self.player);
+		}
+		write!(f, "{}:", self.player)?;
+		for issue in &self.issues {
+			let severity = match issue.severity() {
+				Severity::Error => "error",
+				Severity::Warning => "warning",
+			};
+			write!(f, "\n\t{}: {}", severity, issue)?;
+		}
+		Ok(())
+	}
+}
+
+/// Checks a player against the MPRIS spec, without changing its state:
+/// the interfaces it implements, the presence, types and values of their properties,
+/// and the signals it emits while listening for a moment.
+///
+/// Errors are only returned if the player can't be reached at all.
+pub async fn check(player: &MediaPlayer) -> Result<ComplianceReport> {
+	let interfaces = player.interfaces().await?;
+	let proxy = PropertiesProxy::builder(player.connection())
+		.destination(player.destination().to_owned())?
+		.path(player.path().to_owned())?
+		.build()
+		.await?;
+	let mut issues = Vec::new();
+	if !interfaces.player {
+		issues.push(Issue::MissingInterface(PLAYER));
+	}
+	let mut changes = proxy.receive_properties_changed().await?;
+	let mut values = HashMap::new();
+	for (interface, properties) in PROPERTIES {
+		let implemented = match *interface {
+			PLAYER => interfaces.player,
+			TRACK_LIST => interfaces.track_list,
+			PLAYLISTS => interfaces.playlists,
+			_ => true,
+		};
+		if !implemented {
+			continue;
+		}
+		let all = proxy
+			.get_all(InterfaceName::from_static_str_unchecked(interface))
+			.await
+			.unwrap_or_default();
+		for (property, signature, required) in *properties {
+			// Fallible properties are left out of GetAll, so ask for them one by one.
+			let value = match all.get(*property) {
+				Some(value) => Some(value.clone()),
+				None => proxy
+					.get(
+						InterfaceName::from_static_str_unchecked(interface),
+						property,
+					)
+					.await
+					.ok(),
+			};
+			match value {
+				Some(value) => {
+					let actual = value.value_signature().to_string();
+					if actual == *signature {
+						values.insert(*property, value);
+					} else {
+						issues.push(Issue::IncorrectType {
+							interface,
+							property,
+							expected: signature,
+							actual,
+						});
+					}
+				}
+				None if *required => issues.push(Issue::MissingProperty {
+					interface,
+					property,
+				}),
+				None => {}
+			}
+		}
+	}
+	check_values(&values, &mut issues);
+
+	let start = Timer::after(LISTEN_TIME);
+	pin_mut!(start);
+	loop {
+		let next = changes.next();
+		match future::select(next, &mut start).await {
+			future::Either::Left((Some(signal), _)) => {
+				if let Ok(args) = signal.args() {
+					check_signal(
+						args.interface_name.as_str(),
+						&args.changed_properties,
+						&mut issues,
+					);
+				}
+			}
+			_ => break,
+		}
+	}
+	if interfaces.player {
+		let playing = values
+			.get("PlaybackStatus")
+			.and_then(|status| str_value(status))
+			.is_some_and(|status| status == "Playing");
+		let moving = values
+			.get("Rate")
+			.and_then(|rate| f64::try_from(rate.clone()).ok())
+			.is_some_and(|rate| rate > 0.0);
+		if playing && moving {
+			let before = values
+				.get("Position")
+				.and_then(|position| i64::try_from(position.clone()).ok());
+			let after = proxy
+				.get(InterfaceName::from_static_str_unchecked(PLAYER), "Position")
+				.await
+				.ok()
+				.and_then(|position| i64::try_from(position).ok());
+			let status = proxy
+				.get(
+					InterfaceName::from_static_str_unchecked(PLAYER),
+					"PlaybackStatus",
+				)
+				.await
+				.ok()
+				.and_then(|status| String::try_from(status).ok());
+			// Only judge if the player kept playing the whole time.
+			if let (Some(before), Some(after), Some("Playing")) = (before, after, status.as_deref())
+			{
+				if after <= before {
+					issues.push(Issue::PositionNotAdvancing);
+				}
+			}
+		}
+	}
+
+	Ok(ComplianceReport {
+		player: player.destination().to_string(),
+		interfaces,
+		issues,
+	})
+}
+
+fn check_values(values: &HashMap<&str, OwnedValue>, issues: &mut Vec<Issue>) {
+	let double = |property| {
+		values
+			.get(property)
+			.and_then(|value| f64::try_from(value.clone()).ok())
+	};
+	let mut invalid = |property: &'static str, value: String, reason: &'static str| {
+		issues.push(Issue::InvalidValue {
+			interface: PLAYER,
+			property,
+			value,
+			reason,
+		});
+	};
+	if let Some(status) = values.get("PlaybackStatus").and_then(|v| str_value(v)) {
+		if PlaybackStatus::from_str(status).is_err() {
+			invalid(
+				"PlaybackStatus",
+				status.to_string(),
+				"it isn't a playback status",
+			);
+		}
+	}
+	if let Some(status) = values.get("LoopStatus").and_then(|v| str_value(v)) {
+		if LoopStatus::from_str(status).is_err() {
+			invalid("LoopStatus", status.to_string(), "it isn't a loop status");
+		}
+	}
+	if let Some(minimum) = double("MinimumRate") {
+		if minimum > 1.0 {
+			invalid("MinimumRate", minimum.to_string(), "it must be at most 1");
+		}
+	}
+	if let Some(maximum) = double("MaximumRate") {
+		if maximum < 1.0 {
+			invalid("MaximumRate", maximum.to_string(), "it must be at least 1");
+		}
+	}
+	if let Some(rate) = double("Rate") {
+		let minimum = double("MinimumRate").unwrap_or(rate);
+		let maximum = double("MaximumRate").unwrap_or(rate);
+		if rate.is_nan() || rate < minimum || rate > maximum {
+			invalid(
+				"Rate",
+				rate.to_string(),
+				"it is outside of MinimumRate and MaximumRate",
+			);
+		}
+	}
+	if let Some(volume) = double("Volume") {
+		if volume.is_nan() || volume < 0.0 {
+			invalid("Volume", volume.to_string(), "it must not be negative");
+		}
+	}
+	if let Some(position) = values
+		.get("Position")
+		.and_then(|value| i64::try_from(value.clone()).ok())
+	{
+		if position < 0 {
+			invalid("Position", position.to_string(), "it must not be negative");
+		}
+	}
+	let flag = |property| {
+		values
+			.get(property)
+			.and_then(|value| bool::try_from(value.clone()).ok())
+	};
+	if flag("CanControl") == Some(false) {
+		for property in CONTROLS {
+			if flag(property) == Some(true) {
+				issues.push(Issue::UncontrolledCapability(property));
+			}
+		}
+	}
+	if let Some(metadata) = values.get("Metadata") {
+		let metadata = Metadata::from(
+			HashMap::<String, OwnedValue>::try_from(metadata.clone()).unwrap_or_default(),
+		);
+		if !metadata.as_map().is_empty() {
+			issues.extend(metadata.validate().into_iter().map(Issue::InvalidMetadata));
+		}
+	}
+}
+
+fn check_signal(interface: &str, changed: &HashMap<&str, Value<'_>>, issues: &mut Vec<Issue>) {
+	let Some((interface, properties)) = PROPERTIES.iter().find(|(name, _)| *name == interface)
+	else {
+		return;
+	};
+	for (property, value) in changed {
+		if *interface == PLAYER && *property == "Position" {
+			if !issues.contains(&Issue::PositionChangedSignalled) {
+				issues.push(Issue::PositionChangedSignalled);
+			}
+			continue;
+		}
+		let Some((property, expected, _)) = properties.iter().find(|(name, ..)| name == property)
+		else {
+			continue;
+		};
+		let actual = value.value_signature().to_string();
+		if actual == *expected {
+			continue;
+		}
+		let issue = Issue::IncorrectSignalType {
+			interface,
+			property,
+			expected,
+			actual,
+		};
+		if !issues.contains(&issue) {
+			issues.push(issue);
+		}
+	}
+}
+
+fn str_value(value: &OwnedValue) -> Option<&str> {
+	match &**value {
+		Value::Str(s) => Some(s.as_str()),
+		_ => None,
+	}
+}
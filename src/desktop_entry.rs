@@ -0,0 +1,164 @@
+// SPDX-License-Identifier: MPL-2.0
+use crate::{error::Result, media_player::MediaPlayer};
+use std::{
+	env,
+	path::{Path, PathBuf},
+};
+
+/// The parts of a player's `.desktop` file that are useful to show it in a UI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DesktopEntry {
+	/// The application name, localized for the current locale where available.
+	pub name: String,
+	/// The icon, either an icon theme name or an absolute path.
+	pub icon: Option<String>,
+	/// The `.desktop` file the entry was read from.
+	pub path: PathBuf,
+}
+
+impl DesktopEntry {
+	/// Looks up `<id>.desktop` in the XDG data directories, localizing the name
+	/// for the locale given by `LC_ALL`, `LC_MESSAGES` or `LANG`.
+	///
+	/// Returns None if no such file exists or it has no name.
+	pub fn find(id: &str) -> Option<Self> {
+		let file_name = format!("{}.desktop", id.trim_end_matches(".desktop"));
+		let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+			.into_iter()
+			.filter_map(|var| env::var(var).ok())
+			.find(|locale| !locale.is_empty());
+		data_dirs()
+			.into_iter()
+			.map(|dir| dir.join("applications").join(&file_name))
+			.find_map(|path| Self::read(&path, locale.as_deref()))
+	}
+
+	fn read(path: &Path, locale: Option<&str>) -> Option<Self> {
+		let contents = std::fs::read_to_string(path).ok()?;
+		let mut in_entry = false;
+		let mut names = Vec::new();
+		let mut icon = None;
+		for line in contents.lines().map(str::trim) {
+			if line.starts_with('[') {
+				in_entry = line == "[Desktop Entry]";
+				continue;
+			}
+			if !in_entry || line.starts_with('#') {
+				continue;
+			}
+			let (key, value) = match line.split_once('=') {
+				Some((key, value)) => (key.trim(), unescape(value.trim())),
+				None => continue,
+			};
+			if key == "Icon" {
+				icon = Some(value).filter(|icon| !icon.is_empty());
+			} else if key == "Name" {
+				names.push((None, value));
+			} else if let Some(locale) = key
+				.strip_prefix("Name[")
+				.and_then(|key| key.strip_suffix(']'))
+			{
+				names.push((Some(locale.to_string()), value));
+			}
+		}
+		let name = locale_candidates(locale)
+			.into_iter()
+			.map(Some)
+			.chain([None])
+			.find_map(|candidate| {
+				names
+					.iter()
+					.find(|(locale, _)| *locale == candidate)
+					.map(|(_, name)| name.clone())
+			})?;
+		Some(Self {
+			name,
+			icon,
+			path: path.to_path_buf(),
+		})
+	}
+}
+
+impl MediaPlayer {
+	/// Resolves the player's `DesktopEntry` to its `.desktop` file, for its localized name and icon.
+	///
+	/// Returns None if the player has no desktop entry, or its file can't be found.
+	pub async fn desktop_entry_info(&self) -> Result<Option<DesktopEntry>> {
+		let id = match self.desktop_entry().await {
+			Ok(id) if !id.is_empty() => id,
+			_ => return Ok(None),
+		};
+		Ok(blocking::unblock(move || DesktopEntry::find(&id)).await)
+	}
+}
+
+/// `$XDG_DATA_HOME` followed by `$XDG_DATA_DIRS`, with the spec's defaults.
+fn data_dirs() -> Vec<PathBuf> {
+	let data_home = env::var_os("XDG_DATA_HOME")
+		.map(PathBuf::from)
+		.filter(|path| path.is_absolute())
+		.or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")));
+	let data_dirs = env::var("XDG_DATA_DIRS")
+		.ok()
+		.filter(|dirs| !dirs.is_empty())
+		.unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+	data_home
+		.into_iter()
+		.chain(
+			data_dirs
+				.split(':')
+				.map(PathBuf::from)
+				.filter(|path| path.is_absolute()),
+		)
+		.collect()
+}
+
+/// The `Name[...]` keys to try for a POSIX locale such as `sr_RS.UTF-8@latin`, most specific first.
+fn locale_candidates(locale: Option<&str>) -> Vec<String> {
+	let locale = match locale {
+		Some(locale) if locale != "C" && locale != "POSIX" => locale,
+		_ => return Vec::new(),
+	};
+	let (locale, modifier) = match locale.split_once('@') {
+		Some((locale, modifier)) => (locale, Some(modifier)),
+		None => (locale, None),
+	};
+	let locale = locale.split('.').next().unwrap_or(locale);
+	let (lang, country) = match locale.split_once('_') {
+		Some((lang, country)) => (lang, Some(country)),
+		None => (locale, None),
+	};
+	let mut candidates = Vec::new();
+	if let (Some(country), Some(modifier)) = (country, modifier) {
+		candidates.push(format!("{}_{}@{}", lang, country, modifier));
+	}
+	if let Some(country) = country {
+		candidates.push(format!("{}_{}", lang, country));
+	}
+	if let Some(modifier) = modifier {
+		candidates.push(format!("{}@{}", lang, modifier));
+	}
+	candidates.push(lang.to_string());
+	candidates
+}
+
+/// Resolves the escapes allowed in `.desktop` string values.
+fn unescape(value: &str) -> String {
+	let mut unescaped = String::with_capacity(value.len());
+	let mut chars = value.chars();
+	while let Some(c) = chars.next() {
+		if c != '\\' {
+			unescaped.push(c);
+			continue;
+		}
+		match chars.next() {
+			Some('s') => unescaped.push(' '),
+			Some('n') => unescaped.push('\n'),
+			Some('t') => unescaped.push('\t'),
+			Some('r') => unescaped.push('\r'),
+			Some(c) => unescaped.push(c),
+			None => unescaped.push('\\'),
+		}
+	}
+	unescaped
+}
@@ -15,12 +15,52 @@ pub enum Error {
 		actual: &'static str,
 	},
 
+	/// A metadata field held a different variant than the one requested.
+	#[error(
+		"Tried to read metadata field {key} as a {wanted}, but it was actually {actual} (signature `{signature}`)"
+	)]
+	IncorrectField {
+		key: String,
+		wanted: &'static str,
+		actual: &'static str,
+		signature: String,
+	},
+
+	/// A metadata field needed for an operation was absent.
+	#[error("Metadata field {0} is missing")]
+	MissingField(String),
+
 	#[error("Tried to convert Value::{wanted}, but it was got {actual:?}")]
 	IncorrectValue {
 		wanted: &'static str,
 		actual: zvariant::OwnedValue,
 	},
 
+	/// Metadata to be published deviates from the MPRIS metadata guidelines.
+	#[error("Invalid metadata: {}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "))]
+	InvalidMetadata(Vec<crate::metadata::validation::Violation>),
+
+	/// The given bus name is not in the `org.mpris.MediaPlayer2.` namespace.
+	#[error("{0} is not an MPRIS bus name")]
+	NotAnMprisName(zbus::names::OwnedBusName),
+
+	/// Failed to deserialize metadata into a user-defined type.
+	#[error("Failed to deserialize metadata: {0}")]
+	Deserialize(String),
+
+	/// A URL was malformed, or uses a scheme that isn't supported.
+	#[error("Invalid or unsupported URL: {0}")]
+	InvalidUrl(String),
+
+	/// An I/O error.
+	#[error("I/O error: {0}")]
+	Io(std::io::Error),
+
+	/// An HTTP error while downloading art.
+	#[cfg(feature = "art")]
+	#[error("HTTP error: {0}")]
+	Http(Box<ureq::Error>),
+
 	/// A zbus error.
 	#[error("zbus error: {0}")]
 	Zbus(zbus::Error),
@@ -48,4 +88,16 @@ impl From<zbus::Error> for Error {
 	}
 }
 
+impl From<std::io::Error> for Error {
+	fn from(err: std::io::Error) -> Self {
+		Self::Io(err)
+	}
+}
+
+impl serde::de::Error for Error {
+	fn custom<T: std::fmt::Display>(msg: T) -> Self {
+		Self::Deserialize(msg.to_string())
+	}
+}
+
 pub type Result<T> = std::result::Result<T, Error>;
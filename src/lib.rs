@@ -1,13 +1,32 @@
 // SPDX-License-Identifier: MPL-2.0
+// `Error::IncorrectValue` carries the offending value unboxed, which is part of the public API.
+#![allow(clippy::result_large_err)]
+#[cfg(feature = "art")]
+pub mod art;
 pub mod bindings;
+pub mod compliance;
+#[cfg(feature = "freedesktop")]
+pub mod desktop_entry;
 pub mod error;
 pub mod media_player;
 pub mod metadata;
+#[cfg(feature = "mock")]
+pub mod mock;
+#[cfg(feature = "notify")]
+pub mod notification;
 pub mod player;
+pub mod playerctld;
 pub mod playlists;
+#[cfg(feature = "recorder")]
+pub mod recorder;
+#[cfg(feature = "server")]
+pub mod server;
 pub mod track;
 pub mod track_list;
 
+use std::collections::HashMap;
+use zbus::{fdo::PropertiesProxy, names::OwnedBusName, zvariant::OwnedValue};
+
 pub(crate) fn handle_optional<T>(input: zbus::Result<T>) -> error::Result<Option<T>> {
 	match input {
 		Ok(input) => Ok(Some(input)),
@@ -19,3 +38,30 @@ pub(crate) fn handle_optional<T>(input: zbus::Result<T>) -> error::Result<Option
 		Err(err) => Err(error::Error::from(err)),
 	}
 }
+
+/// The prefix shared by the bus names of all MPRIS media players.
+pub(crate) const MPRIS_PREFIX: &str = "org.mpris.MediaPlayer2.";
+
+pub(crate) fn check_mpris_name(name: OwnedBusName) -> error::Result<OwnedBusName> {
+	if name.starts_with(MPRIS_PREFIX) {
+		Ok(name)
+	} else {
+		Err(error::Error::NotAnMprisName(name))
+	}
+}
+
+/// Fetches every property of the proxy's interface in a single `GetAll` call,
+/// so the values are consistent with each other.
+pub(crate) async fn get_all_properties(
+	proxy: &zbus::Proxy<'_>,
+) -> error::Result<HashMap<String, OwnedValue>> {
+	let properties = PropertiesProxy::builder(proxy.connection())
+		.destination(proxy.destination().to_owned())?
+		.path(proxy.path().to_owned())?
+		.build()
+		.await?;
+	properties
+		.get_all(proxy.interface().to_owned())
+		.await
+		.map_err(error::Error::from)
+}
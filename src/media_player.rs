@@ -1,16 +1,91 @@
 // SPDX-License-Identifier: MPL-2.0
+pub mod active;
+pub mod discovery;
+pub mod name;
+pub mod selector;
+
+use self::name::MprisName;
 use crate::{
 	bindings::{
 		media_player::MediaPlayer2Proxy, player::PlayerProxy, playlist::PlaylistsProxy,
 		track_list::TrackListProxy,
 	},
+	check_mpris_name,
 	error::{Error, Result},
-	player::Player,
+	get_all_properties, handle_optional,
+	metadata::MetadataValue,
+	player::{PlaybackStatus, Player},
 	playlists::Playlists,
 	track_list::TrackList,
+	MPRIS_PREFIX,
+};
+use async_io::Timer;
+use futures_util::{future, pin_mut, stream::BoxStream, StreamExt};
+use std::{
+	collections::HashMap,
+	fmt::{self, Display},
+	hash::{Hash, Hasher},
+	ops::Deref,
+	time::Duration,
 };
-use std::ops::Deref;
-use zbus::{fdo::DBusProxy, names::OwnedBusName, Connection};
+use zbus::{
+	fdo::{DBusProxy, IntrospectableProxy},
+	names::{BusName, OwnedBusName},
+	Connection, ConnectionBuilder,
+};
+
+/// The properties of a player, as returned by [MediaPlayer::info].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MediaPlayerInfo {
+	/// A friendly name to identify the player to users.
+	pub identity: String,
+	/// The basename of the player's `.desktop` file, if it has one.
+	pub desktop_entry: Option<String>,
+	/// The URI schemes the player can open.
+	pub supported_uri_schemes: Vec<String>,
+	/// The MIME types the player can open.
+	pub supported_mime_types: Vec<String>,
+	pub can_quit: bool,
+	pub can_raise: bool,
+	pub can_set_fullscreen: bool,
+	/// Whether the player is fullscreen, if it supports fullscreen.
+	pub fullscreen: Option<bool>,
+	pub has_track_list: bool,
+}
+
+/// What a player allows to be done with it, as returned by [MediaPlayer::capabilities].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct MediaPlayerCapabilities {
+	pub can_quit: bool,
+	pub can_raise: bool,
+	pub can_set_fullscreen: bool,
+	pub has_track_list: bool,
+}
+
+impl From<&MediaPlayerInfo> for MediaPlayerCapabilities {
+	fn from(info: &MediaPlayerInfo) -> Self {
+		Self {
+			can_quit: info.can_quit,
+			can_raise: info.can_raise,
+			can_set_fullscreen: info.can_set_fullscreen,
+			has_track_list: info.has_track_list,
+		}
+	}
+}
+
+/// The interfaces a player's object implements, as returned by [MediaPlayer::interfaces].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MediaPlayerInterfaces {
+	/// `org.mpris.MediaPlayer2.Player`
+	pub player: bool,
+	/// `org.mpris.MediaPlayer2.TrackList`
+	pub track_list: bool,
+	/// `org.mpris.MediaPlayer2.Playlists`
+	pub playlists: bool,
+	/// Interfaces which are neither MPRIS nor standard D-Bus interfaces,
+	/// such as vendor extensions.
+	pub extensions: Vec<String>,
+}
 
 #[derive(Debug, Clone)]
 pub struct MediaPlayer {
@@ -21,13 +96,137 @@ impl MediaPlayer {
 	/// Creates a new instance of the `org.mpris.MediaPlayer2` interface.
 	pub async fn new(connection: &Connection, name: OwnedBusName) -> Result<Self> {
 		MediaPlayer2Proxy::builder(connection)
-			.destination(name)?
+			.destination(check_mpris_name(name)?)?
 			.build()
 			.await
 			.map(Self::from)
 			.map_err(Error::from)
 	}
 
+	/// The player's bus name, split into its application and instance parts.
+	pub fn mpris_name(&self) -> Result<MprisName> {
+		MprisName::try_from(OwnedBusName::from(self.proxy.destination().to_owned()))
+	}
+
+	/// Gets the player's identity, desktop entry, supported schemes and MIME types,
+	/// and capabilities with a single call.
+	pub async fn info(&self) -> Result<MediaPlayerInfo> {
+		let properties = get_all_properties(self.proxy.inner()).await?;
+		let get = |key: &str| {
+			properties
+				.get(key)
+				.map(|value| MetadataValue::from(&**value))
+		};
+		let string = |key: &str| get(key).and_then(|value| value.try_into_string().ok());
+		let strings = |key: &str| {
+			get(key)
+				.and_then(|value| Vec::<String>::try_from(value).ok())
+				.unwrap_or_default()
+		};
+		let boolean = |key: &str| get(key).and_then(|value| value.try_into_bool().ok());
+		Ok(MediaPlayerInfo {
+			identity: string("Identity").unwrap_or_default(),
+			desktop_entry: string("DesktopEntry"),
+			supported_uri_schemes: strings("SupportedUriSchemes"),
+			supported_mime_types: strings("SupportedMimeTypes"),
+			can_quit: boolean("CanQuit").unwrap_or_default(),
+			can_raise: boolean("CanRaise").unwrap_or_default(),
+			can_set_fullscreen: boolean("CanSetFullscreen").unwrap_or_default(),
+			fullscreen: boolean("Fullscreen"),
+			has_track_list: boolean("HasTrackList").unwrap_or_default(),
+		})
+	}
+
+	/// Gets what the player allows to be done with it, with a single call.
+	pub async fn capabilities(&self) -> Result<MediaPlayerCapabilities> {
+		self.info()
+			.await
+			.map(|info| MediaPlayerCapabilities::from(&info))
+	}
+
+	/// Brings the player's user interface to the front.
+	///
+	/// Returns false without calling the player if it can't be raised.
+	pub async fn raise_checked(&self) -> Result<bool> {
+		if self.proxy.can_raise().await? {
+			self.proxy.raise().await?;
+			Ok(true)
+		} else {
+			Ok(false)
+		}
+	}
+
+	/// Asks the player to quit.
+	///
+	/// Returns false without calling the player if it can't be quit.
+	pub async fn quit_checked(&self) -> Result<bool> {
+		if self.proxy.can_quit().await? {
+			self.proxy.quit().await?;
+			Ok(true)
+		} else {
+			Ok(false)
+		}
+	}
+
+	/// Whether the player's media is being shown fullscreen.
+	///
+	/// Not all players support this, and it will return None if this is the case.
+	pub async fn fullscreen(&self) -> Result<Option<bool>> {
+		handle_optional(self.proxy.fullscreen().await)
+	}
+
+	/// Shows or stops showing the player's media fullscreen.
+	///
+	/// Returns false without calling the player if it doesn't allow this.
+	pub async fn set_fullscreen(&self, value: bool) -> Result<bool> {
+		if handle_optional(self.proxy.can_set_fullscreen().await)?.unwrap_or(false) {
+			self.proxy.set_fullscreen(value).await?;
+			Ok(true)
+		} else {
+			Ok(false)
+		}
+	}
+
+	/// Toggles whether the player's media is shown fullscreen.
+	///
+	/// Returns false without calling the player if it doesn't support fullscreen.
+	pub async fn toggle_fullscreen(&self) -> Result<bool> {
+		match self.fullscreen().await? {
+			Some(fullscreen) => self.set_fullscreen(!fullscreen).await,
+			None => Ok(false),
+		}
+	}
+
+	/// Returns a stream of the player's `Fullscreen` values as they change,
+	/// starting with the current one. Values which fail to be read are skipped.
+	pub async fn fullscreen_changes(&self) -> BoxStream<'static, bool> {
+		self.proxy
+			.receive_fullscreen_changed()
+			.await
+			.filter_map(|change| async move { change.get().await.ok() })
+			.boxed()
+	}
+
+	/// Returns a stream of the player's `Identity` as it changes, starting with the current one,
+	/// e.g. as a browser switches between tabs. Values which fail to be read are skipped.
+	pub async fn identity_changes(&self) -> BoxStream<'static, String> {
+		self.proxy
+			.receive_identity_changed()
+			.await
+			.filter_map(|change| async move { change.get().await.ok() })
+			.boxed()
+	}
+
+	/// Returns a stream of the player's `SupportedUriSchemes` as they change,
+	/// starting with the current ones. Values which fail to be read are skipped.
+	pub async fn supported_uri_schemes_changes(&self) -> BoxStream<'static, Vec<String>> {
+		self.proxy
+			.receive_supported_uri_schemes_changed()
+			.await
+			.filter_map(|change| async move { change.get().await.ok() })
+			.boxed()
+	}
+
 	/// Gets the names of all the MPRIS players that are available on the current session.
 	pub async fn available_players(connection: &Connection) -> Result<Vec<OwnedBusName>> {
 		let dbus = DBusProxy::builder(connection)
@@ -36,13 +235,40 @@ impl MediaPlayer {
 			.await?;
 		let mut players = Vec::new();
 		for name in dbus.list_names().await? {
-			if name.starts_with("org.mpris.MediaPlayer2.") {
+			if name.starts_with(MPRIS_PREFIX) {
 				players.push(name);
 			}
 		}
 		Ok(players)
 	}
 
+	/// Gets the names of the MPRIS players that can be started through D-Bus activation,
+	/// whether or not they are running.
+	pub async fn activatable_players(connection: &Connection) -> Result<Vec<OwnedBusName>> {
+		let dbus = DBusProxy::new(connection).await?;
+		Ok(dbus
+			.list_activatable_names()
+			.await?
+			.into_iter()
+			.filter(|name| name.starts_with(MPRIS_PREFIX))
+			.collect())
+	}
+
+	/// Starts the player with the given bus name through D-Bus activation,
+	/// unless it is already running, and returns an instance to it.
+	pub async fn launch(connection: &Connection, name: OwnedBusName) -> Result<Self> {
+		let name = check_mpris_name(name)?;
+		let well_known = match &*name {
+			BusName::WellKnown(name) => name.to_owned(),
+			BusName::Unique(_) => return Err(Error::NotAnMprisName(name)),
+		};
+		DBusProxy::new(connection)
+			.await?
+			.start_service_by_name(well_known, 0)
+			.await?;
+		Self::new(connection, name).await
+	}
+
 	/// Gets a new instance of all the MPRIS players that are available on the current session.
 	pub async fn new_all(connection: &Connection) -> Result<Vec<Self>> {
 		let players = Self::available_players(connection).await?;
@@ -53,6 +279,195 @@ impl MediaPlayer {
 		Ok(instances)
 	}
 
+	/// Connects to the session bus and gets a new instance of all the MPRIS players on it,
+	/// for tools which don't otherwise need a [Connection].
+	pub async fn session_all() -> Result<Vec<Self>> {
+		Self::new_all(&Connection::session().await?).await
+	}
+
+	/// Connects to the bus at the given D-Bus address and gets a new instance of all
+	/// the MPRIS players on it.
+	pub async fn address_all(address: &str) -> Result<Vec<Self>> {
+		Self::new_all(&ConnectionBuilder::address(address)?.build().await?).await
+	}
+
+	/// Gets a new instance of all the MPRIS players that are available on the current session,
+	/// collecting the players that failed instead of giving up on the first error,
+	/// e.g. when a player quits while the players are being enumerated.
+	pub async fn new_all_lossy(
+		connection: &Connection,
+	) -> Result<(Vec<Self>, Vec<(OwnedBusName, Error)>)> {
+		let players = Self::available_players(connection).await?;
+		let mut instances = Vec::with_capacity(players.len());
+		let mut failures = Vec::new();
+		for player in players {
+			match Self::new(connection, player.clone()).await {
+				Ok(instance) => instances.push(instance),
+				Err(err) => failures.push((player, err)),
+			}
+		}
+		Ok((instances, failures))
+	}
+
+	/// Waits for a player whose name satisfies `matcher` to appear on the bus,
+	/// e.g. after launching it, and returns an instance to it.
+	///
+	/// Players which are already running are matched too.
+	/// Returns None if no player matched within `timeout`.
+	pub async fn wait_for<F>(
+		connection: &Connection,
+		mut matcher: F,
+		timeout: Duration,
+	) -> Result<Option<Self>>
+	where
+		F: FnMut(&MprisName) -> bool,
+	{
+		let timer = Timer::after(timeout);
+		// Subscribe before listing, so a player appearing in between isn't missed.
+		let appeared = DBusProxy::new(connection)
+			.await?
+			.receive_name_owner_changed()
+			.await?
+			.filter_map(|signal| {
+				let name = signal
+					.args()
+					.ok()
+					.filter(|args| args.new_owner().is_some())
+					.and_then(|args| {
+						MprisName::try_from(OwnedBusName::from(args.name().to_owned())).ok()
+					});
+				future::ready(name)
+			});
+		pin_mut!(appeared);
+		let mut running = Self::available_players(connection)
+			.await?
+			.into_iter()
+			.filter_map(|name| MprisName::try_from(name).ok());
+		let mut found = running.find(&mut matcher);
+		if found.is_none() {
+			let wait = async {
+				while let Some(name) = appeared.next().await {
+					if matcher(&name) {
+						return Some(name);
+					}
+				}
+				None
+			};
+			let timeout = async {
+				timer.await;
+				None
+			};
+			pin_mut!(wait, timeout);
+			found = future::select(wait, timeout).await.factor_first().0;
+		}
+		match found {
+			Some(name) => Self::new(connection, name.into_inner()).await.map(Some),
+			None => Ok(None),
+		}
+	}
+
+	/// Gets all players grouped by application, so multiple instances such as browser tabs
+	/// can be presented together.
+	///
+	/// Players are keyed by their `DesktopEntry`, or by the application part of their bus name
+	/// if they have none.
+	pub async fn group_by_application(
+		connection: &Connection,
+	) -> Result<HashMap<String, Vec<Self>>> {
+		let mut groups = HashMap::<String, Vec<Self>>::new();
+		for media_player in Self::new_all_lossy(connection).await?.0 {
+			let desktop_entry = media_player.desktop_entry().await.ok();
+			let key = application_key(desktop_entry, &media_player.mpris_name()?);
+			groups.entry(key).or_default().push(media_player);
+		}
+		Ok(groups)
+	}
+
+	/// Finds the players whose `Identity` matches `identity`, ignoring case.
+	///
+	/// Players that don't answer are skipped.
+	pub async fn find_by_identity(connection: &Connection, identity: &str) -> Result<Vec<Self>> {
+		let mut matches = Vec::new();
+		for player in Self::new_all_lossy(connection).await?.0 {
+			if player
+				.identity()
+				.await
+				.is_ok_and(|id| id.eq_ignore_ascii_case(identity))
+			{
+				matches.push(player);
+			}
+		}
+		Ok(matches)
+	}
+
+	/// Finds the players whose `DesktopEntry` matches `desktop_entry`, ignoring case.
+	///
+	/// Players that don't answer or have no desktop entry are skipped.
+	pub async fn find_by_desktop_entry(
+		connection: &Connection,
+		desktop_entry: &str,
+	) -> Result<Vec<Self>> {
+		let mut matches = Vec::new();
+		for player in Self::new_all_lossy(connection).await?.0 {
+			if player
+				.desktop_entry()
+				.await
+				.is_ok_and(|entry| entry.eq_ignore_ascii_case(desktop_entry))
+			{
+				matches.push(player);
+			}
+		}
+		Ok(matches)
+	}
+
+	/// Gets the players that are currently playing.
+	pub async fn playing(connection: &Connection) -> Result<Vec<Self>> {
+		Self::filter_by_status(connection, PlaybackStatus::Playing).await
+	}
+
+	/// Gets the players whose playback status is `status`, querying them concurrently.
+	///
+	/// Players that don't answer are skipped.
+	pub async fn filter_by_status(
+		connection: &Connection,
+		status: PlaybackStatus,
+	) -> Result<Vec<Self>> {
+		let players = Self::new_all_lossy(connection).await?.0;
+		let statuses = future::join_all(players.iter().map(|media_player| async move {
+			media_player.player().await?.playback_status().await
+		}))
+		.await;
+		Ok(players
+			.into_iter()
+			.zip(statuses)
+			.filter(|(_, actual)| actual.as_ref().is_ok_and(|actual| *actual == status))
+			.map(|(media_player, _)| media_player)
+			.collect())
+	}
+
+	/// Finds the player whose current track has `url` as its `xesam:url` or `mpris:trackid`,
+	/// e.g. to control the browser tab playing a given video.
+	///
+	/// Players that don't answer are skipped.
+	pub async fn find_by_url(connection: &Connection, url: &str) -> Result<Option<Self>> {
+		for media_player in Self::new_all_lossy(connection).await?.0 {
+			let metadata = match media_player.player().await {
+				Ok(player) => player.metadata().await,
+				Err(err) => Err(err),
+			};
+			let matches = metadata.is_ok_and(|metadata| {
+				metadata.url().as_deref() == Some(url)
+					|| metadata
+						.track_id()
+						.is_some_and(|track_id| track_id.as_str() == url)
+			});
+			if matches {
+				return Ok(Some(media_player));
+			}
+		}
+		Ok(None)
+	}
+
 	/// Returns an instance to the `org.mpris.MediaPlayer2.Player` interface of this object.
 	pub async fn player(&self) -> Result<Player> {
 		PlayerProxy::builder(self.proxy.connection())
@@ -79,10 +494,39 @@ impl MediaPlayer {
 		}
 	}
 
+	/// Introspects the player's object to find out which interfaces it implements,
+	/// so clients can adapt up front instead of probing with failing calls.
+	pub async fn interfaces(&self) -> Result<MediaPlayerInterfaces> {
+		let introspection = IntrospectableProxy::builder(self.proxy.connection())
+			.destination(self.proxy.destination().to_owned())?
+			.path(self.proxy.path().to_owned())?
+			.build()
+			.await?
+			.introspect()
+			.await?;
+		let node = introspection
+			.parse::<zbus::xml::Node>()
+			.map_err(|err| zbus::Error::Failure(format!("Invalid introspection data: {}", err)))?;
+		let mut interfaces = MediaPlayerInterfaces::default();
+		for interface in node.interfaces() {
+			match interface.name() {
+				"org.mpris.MediaPlayer2" => {}
+				"org.mpris.MediaPlayer2.Player" => interfaces.player = true,
+				"org.mpris.MediaPlayer2.TrackList" => interfaces.track_list = true,
+				"org.mpris.MediaPlayer2.Playlists" => interfaces.playlists = true,
+				name if name.starts_with("org.freedesktop.DBus.") => {}
+				name => interfaces.extensions.push(name.to_owned()),
+			}
+		}
+		Ok(interfaces)
+	}
+
 	/// Returns an instance to the `org.mpris.MediaPlayer2.Playlists` interface of this object,
-	/// if a track list is available.
+	/// if the player implements it.
+	///
+	/// There is no `HasPlaylists` property, so this introspects the player's object.
 	pub async fn playlists(&self) -> Result<Option<Playlists>> {
-		if self.proxy.has_track_list().await? {
+		if self.interfaces().await?.playlists {
 			PlaylistsProxy::builder(self.proxy.connection())
 				.destination(self.proxy.destination().to_owned())?
 				.build()
@@ -96,6 +540,40 @@ impl MediaPlayer {
 	}
 }
 
+/// The key [MediaPlayer::group_by_application] groups a player under.
+fn application_key(desktop_entry: Option<String>, name: &MprisName) -> String {
+	match desktop_entry {
+		Some(desktop_entry) if !desktop_entry.is_empty() => desktop_entry,
+		_ => name.application().to_string(),
+	}
+}
+
+/// Players are equal when they have the same bus name.
+impl PartialEq for MediaPlayer {
+	fn eq(&self, other: &Self) -> bool {
+		self.proxy.destination() == other.proxy.destination()
+	}
+}
+
+impl Eq for MediaPlayer {}
+
+impl Hash for MediaPlayer {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.proxy.destination().hash(state);
+	}
+}
+
+/// Shows the player's `Identity` once an earlier property read has cached it,
+/// or else its bus name.
+impl Display for MediaPlayer {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self.proxy.cached_identity() {
+			Ok(Some(identity)) => f.write_str(&identity),
+			_ => f.write_str(self.proxy.destination()),
+		}
+	}
+}
+
 impl Deref for MediaPlayer {
 	type Target = MediaPlayer2Proxy<'static>;
 
@@ -109,3 +587,36 @@ impl From<MediaPlayer2Proxy<'static>> for MediaPlayer {
 		Self { proxy }
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::str::FromStr;
+
+	#[test]
+	fn groups_by_desktop_entry_or_application() {
+		let key = |desktop_entry: Option<&str>, name| {
+			application_key(
+				desktop_entry.map(str::to_string),
+				&MprisName::from_str(name).unwrap(),
+			)
+		};
+		assert_eq!(
+			key(None, "org.mpris.MediaPlayer2.org.gnome.Music"),
+			"org.gnome.Music"
+		);
+		assert_eq!(
+			key(None, "org.mpris.MediaPlayer2.org.kde.elisa"),
+			"org.kde.elisa"
+		);
+		assert_eq!(key(None, "org.mpris.MediaPlayer2.vlc.instance1234"), "vlc");
+		assert_eq!(key(Some(""), "org.mpris.MediaPlayer2.vlc"), "vlc");
+		assert_eq!(
+			key(
+				Some("firefox"),
+				"org.mpris.MediaPlayer2.firefox.instance_1_84"
+			),
+			"firefox"
+		);
+	}
+}
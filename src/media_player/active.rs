@@ -0,0 +1,201 @@
+// SPDX-License-Identifier: MPL-2.0
+use crate::{
+	error::Result,
+	player::{PlaybackStatus, Player},
+	MPRIS_PREFIX,
+};
+use futures_util::{
+	future,
+	stream::{self, BoxStream},
+	Stream, StreamExt,
+};
+use std::{collections::HashMap, fmt, str::FromStr};
+use zbus::{
+	fdo::DBusProxy,
+	names::{BusName, OwnedBusName, OwnedUniqueName},
+	zvariant::OwnedValue,
+	Connection, MatchRule, MessageStream, MessageType,
+};
+
+enum Event {
+	OwnerChanged(OwnedBusName, Option<OwnedUniqueName>),
+	StatusChanged(OwnedUniqueName, PlaybackStatus),
+}
+
+#[derive(Debug)]
+struct Entry {
+	name: OwnedBusName,
+	owner: OwnedUniqueName,
+	status: PlaybackStatus,
+}
+
+/// Keeps track of which MPRIS player is the active one, in the way `playerctld` does:
+/// the most recently started player which is playing, or else the player which
+/// most recently started playing or appeared on the bus.
+pub struct ActivePlayerTracker {
+	connection: Connection,
+	/// The players, most recently active first.
+	players: Vec<Entry>,
+	events: BoxStream<'static, Event>,
+}
+
+impl ActivePlayerTracker {
+	/// Subscribes to player changes, then looks up the players which are already running.
+	pub async fn new(connection: &Connection) -> Result<Self> {
+		let dbus = DBusProxy::new(connection).await?;
+		let owners = dbus
+			.receive_name_owner_changed()
+			.await?
+			.filter_map(|signal| {
+				let event = signal.args().ok().and_then(|args| {
+					let name = OwnedBusName::from(args.name().to_owned());
+					name.starts_with(MPRIS_PREFIX).then(|| {
+						let owner = args
+							.new_owner()
+							.as_ref()
+							.map(|owner| owner.to_owned().into());
+						Event::OwnerChanged(name, owner)
+					})
+				});
+				future::ready(event)
+			});
+		let rule = MatchRule::builder()
+			.msg_type(MessageType::Signal)
+			.interface("org.freedesktop.DBus.Properties")?
+			.member("PropertiesChanged")?
+			.path("/org/mpris/MediaPlayer2")?
+			.arg(0, "org.mpris.MediaPlayer2.Player")?
+			.build();
+		let statuses = MessageStream::for_match_rule(rule, connection, None)
+			.await?
+			.filter_map(|message| {
+				let event = message.ok().and_then(|message| {
+					let header = message.header().ok()?;
+					let sender = OwnedUniqueName::from(header.sender().ok()??.to_owned());
+					let (_, changed, _) = message
+						.body::<(String, HashMap<String, OwnedValue>, Vec<String>)>()
+						.ok()?;
+					let status = String::try_from(changed.get("PlaybackStatus")?.clone()).ok()?;
+					let status = PlaybackStatus::from_str(&status).ok()?;
+					Some(Event::StatusChanged(sender, status))
+				});
+				future::ready(event)
+			});
+		let mut tracker = Self {
+			connection: connection.clone(),
+			players: Vec::new(),
+			events: stream::select(owners.boxed(), statuses.boxed()).boxed(),
+		};
+		for name in dbus.list_names().await? {
+			if !name.starts_with(MPRIS_PREFIX) {
+				continue;
+			}
+			if let Ok(owner) = dbus.get_name_owner(BusName::from(name.clone())).await {
+				let entry = tracker.entry(name, owner).await;
+				tracker.players.push(entry);
+			}
+		}
+		// Keep playing players ahead of the rest, in the order the bus listed them.
+		tracker
+			.players
+			.sort_by_key(|entry| entry.status != PlaybackStatus::Playing);
+		Ok(tracker)
+	}
+
+	/// The bus name of the active player, if there are any players.
+	pub fn active(&self) -> Option<&OwnedBusName> {
+		self.players
+			.iter()
+			.find(|entry| entry.status == PlaybackStatus::Playing)
+			.or_else(|| self.players.first())
+			.map(|entry| &entry.name)
+	}
+
+	/// The active player, if there are any players.
+	pub async fn active_player(&self) -> Result<Option<Player>> {
+		match self.active() {
+			Some(name) => Player::new(&self.connection, name.clone()).await.map(Some),
+			None => Ok(None),
+		}
+	}
+
+	/// The bus names of all players, most recently active first.
+	pub fn players(&self) -> impl Iterator<Item = &OwnedBusName> {
+		self.players.iter().map(|entry| &entry.name)
+	}
+
+	/// Returns a stream that yields the new active player every time it changes,
+	/// which is None once no players are left.
+	///
+	/// Unlike property streams, the current player isn't yielded first; see [ActivePlayerTracker::active].
+	/// The stream ends once the connection is closed.
+	pub fn receive_active_changed(&mut self) -> impl Stream<Item = Option<OwnedBusName>> + '_ {
+		stream::unfold(self, |tracker| async move {
+			let change = tracker.next_change().await?;
+			Some((change, tracker))
+		})
+	}
+
+	/// Waits until the active player changes, returning the new one,
+	/// which is None once no players are left.
+	///
+	/// Returns None once the connection is closed.
+	pub async fn next_change(&mut self) -> Option<Option<OwnedBusName>> {
+		let mut active = self.active().cloned();
+		while let Some(event) = self.events.next().await {
+			match event {
+				Event::OwnerChanged(name, owner) => {
+					self.players.retain(|entry| entry.name != name);
+					if let Some(owner) = owner {
+						let entry = self.entry(name, owner).await;
+						self.players.insert(0, entry);
+					}
+				}
+				Event::StatusChanged(sender, status) => {
+					let index = self.players.iter().position(|entry| entry.owner == sender);
+					if let Some(index) = index {
+						let mut entry = self.players.remove(index);
+						let started = status == PlaybackStatus::Playing
+							&& entry.status != PlaybackStatus::Playing;
+						entry.status = status;
+						match started {
+							true => self.players.insert(0, entry),
+							false => self.players.insert(index, entry),
+						}
+					}
+				}
+			}
+			let current = self.active().cloned();
+			if current != active {
+				return Some(current);
+			}
+			active = current;
+		}
+		None
+	}
+
+	/// Looks up the playback status of a player.
+	async fn entry(&self, name: OwnedBusName, owner: OwnedUniqueName) -> Entry {
+		let status = match Player::new(&self.connection, name.clone()).await {
+			Ok(player) => player
+				.playback_status()
+				.await
+				.unwrap_or(PlaybackStatus::Stopped),
+			Err(_) => PlaybackStatus::Stopped,
+		};
+		Entry {
+			name,
+			owner,
+			status,
+		}
+	}
+}
+
+impl fmt::Debug for ActivePlayerTracker {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("ActivePlayerTracker")
+			.field("connection", &self.connection)
+			.field("players", &self.players)
+			.finish_non_exhaustive()
+	}
+}
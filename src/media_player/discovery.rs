@@ -0,0 +1,156 @@
+// SPDX-License-Identifier: MPL-2.0
+use super::MediaPlayer;
+use crate::{check_mpris_name, error::Result, MPRIS_PREFIX};
+use futures_util::{future, StreamExt};
+use std::{
+	collections::BTreeSet,
+	sync::{Arc, Mutex, MutexGuard},
+};
+use zbus::{
+	fdo::DBusProxy,
+	names::{BusName, OwnedBusName},
+	Connection,
+};
+
+/// Applications commonly found on the desktop, whose bus names are probed by default.
+pub const DEFAULT_PROBES: &[&str] = &[
+	"audacious",
+	"celluloid",
+	"chromium",
+	"clementine",
+	"elisa",
+	"firefox",
+	"gnome-music",
+	"Lollypop",
+	"mpv",
+	"rhythmbox",
+	"spotify",
+	"strawberry",
+	"totem",
+	"vlc",
+];
+
+/// Finds players in environments where `ListNames` doesn't list them,
+/// such as Flatpak and Snap sandboxes, which filter it down to the names
+/// the application is allowed to talk to.
+///
+/// Besides listing names, it probes a list of well-known names with `NameHasOwner`,
+/// and remembers the players seen appearing while [PlayerDiscovery::watch] runs.
+/// Players with an instance suffix can only be found by the latter two.
+///
+/// Clones share the players seen, so [PlayerDiscovery::watch] can run on one clone
+/// while another is queried.
+#[derive(Debug, Clone)]
+pub struct PlayerDiscovery {
+	connection: Connection,
+	probes: Vec<OwnedBusName>,
+	seen: Arc<Mutex<BTreeSet<OwnedBusName>>>,
+}
+
+impl PlayerDiscovery {
+	/// Creates a discovery probing the names of the [DEFAULT_PROBES] applications.
+	pub fn new(connection: &Connection) -> Self {
+		let probes = DEFAULT_PROBES
+			.iter()
+			.filter_map(|application| {
+				OwnedBusName::try_from(format!("{MPRIS_PREFIX}{application}")).ok()
+			})
+			.collect();
+		Self {
+			connection: connection.clone(),
+			probes,
+			seen: Arc::default(),
+		}
+	}
+
+	/// Creates a discovery which doesn't probe any names.
+	pub fn without_probes(connection: &Connection) -> Self {
+		Self {
+			probes: Vec::new(),
+			..Self::new(connection)
+		}
+	}
+
+	/// Adds a well-known name to probe, e.g. `org.mpris.MediaPlayer2.myplayer`.
+	pub fn probe(mut self, name: OwnedBusName) -> Result<Self> {
+		let name = check_mpris_name(name)?;
+		if !self.probes.contains(&name) {
+			self.probes.push(name);
+		}
+		Ok(self)
+	}
+
+	/// The well-known names which are probed.
+	pub fn probes(&self) -> &[OwnedBusName] {
+		&self.probes
+	}
+
+	/// Gets the names of the players found by listing names, probing,
+	/// and watching, without duplicates.
+	///
+	/// Probes which fail, e.g. because the sandbox denies them, are skipped.
+	pub async fn players(&self) -> Result<Vec<OwnedBusName>> {
+		let dbus = DBusProxy::new(&self.connection).await?;
+		let mut players = MediaPlayer::available_players(&self.connection)
+			.await
+			.unwrap_or_default();
+		players.extend(self.seen().iter().cloned());
+		let probed = future::join_all(self.probes.iter().map(|name| {
+			let dbus = &dbus;
+			async move {
+				let owned = dbus.name_has_owner(BusName::from(name)).await;
+				owned.is_ok_and(|owned| owned).then(|| name.clone())
+			}
+		}))
+		.await;
+		players.extend(probed.into_iter().flatten());
+		let mut unique = BTreeSet::new();
+		players.retain(|name| unique.insert(name.clone()));
+		Ok(players)
+	}
+
+	/// Gets a new instance of all the players found by [PlayerDiscovery::players],
+	/// skipping the ones that fail.
+	pub async fn media_players(&self) -> Result<Vec<MediaPlayer>> {
+		let mut instances = Vec::new();
+		for name in self.players().await? {
+			if let Ok(instance) = MediaPlayer::new(&self.connection, name).await {
+				instances.push(instance);
+			}
+		}
+		Ok(instances)
+	}
+
+	/// Remembers the players appearing on the bus, and forgets them when they leave,
+	/// until the connection is closed.
+	///
+	/// Sandboxes still deliver `NameOwnerChanged` for the names the application may talk to,
+	/// including ones with an instance suffix which can't be probed.
+	pub async fn watch(&self) -> Result<()> {
+		let mut changes = DBusProxy::new(&self.connection)
+			.await?
+			.receive_name_owner_changed()
+			.await?;
+		while let Some(signal) = changes.next().await {
+			let Ok(args) = signal.args() else {
+				continue;
+			};
+			let name = OwnedBusName::from(args.name().to_owned());
+			if !name.starts_with(MPRIS_PREFIX) {
+				continue;
+			}
+			if args.new_owner().is_some() {
+				self.seen().insert(name);
+			} else {
+				self.seen().remove(&name);
+			}
+		}
+		Ok(())
+	}
+
+	fn seen(&self) -> MutexGuard<'_, BTreeSet<OwnedBusName>> {
+		self.seen
+			.lock()
+			.unwrap_or_else(|poisoned| poisoned.into_inner())
+	}
+}
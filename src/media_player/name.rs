@@ -0,0 +1,120 @@
+// SPDX-License-Identifier: MPL-2.0
+use crate::{
+	error::{Error, Result},
+	MPRIS_PREFIX,
+};
+use std::{
+	fmt::{self, Display},
+	str::FromStr,
+};
+use zbus::names::OwnedBusName;
+
+/// An MPRIS bus name, split into its application and instance parts,
+/// e.g. `org.mpris.MediaPlayer2.vlc.instance1234` into `vlc` and `instance1234`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MprisName(OwnedBusName);
+
+impl MprisName {
+	/// The application part of the name, e.g. `vlc` or `org.gnome.Music`.
+	pub fn application(&self) -> &str {
+		self.split().0
+	}
+
+	/// The instance suffix of the name, e.g. `instance1234`,
+	/// for players which can run more than once.
+	pub fn instance(&self) -> Option<&str> {
+		self.split().1
+	}
+
+	/// Splits off a trailing `instance<digits>` component; everything before it,
+	/// dots included, is the application.
+	fn split(&self) -> (&str, Option<&str>) {
+		let name = &self.0[MPRIS_PREFIX.len()..];
+		match name.rsplit_once('.') {
+			Some((application, instance)) if is_instance(instance) => (application, Some(instance)),
+			_ => (name, None),
+		}
+	}
+
+	/// The full bus name.
+	pub fn bus_name(&self) -> &OwnedBusName {
+		&self.0
+	}
+
+	pub fn into_inner(self) -> OwnedBusName {
+		self.0
+	}
+}
+
+fn is_instance(component: &str) -> bool {
+	component
+		.strip_prefix("instance")
+		.is_some_and(|digits| !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()))
+}
+
+impl TryFrom<OwnedBusName> for MprisName {
+	type Error = Error;
+
+	fn try_from(name: OwnedBusName) -> Result<Self> {
+		if name.len() > MPRIS_PREFIX.len() && name.starts_with(MPRIS_PREFIX) {
+			Ok(Self(name))
+		} else {
+			Err(Error::NotAnMprisName(name))
+		}
+	}
+}
+
+impl FromStr for MprisName {
+	type Err = Error;
+
+	fn from_str(s: &str) -> Result<Self> {
+		OwnedBusName::try_from(s)
+			.map_err(|err| Error::Zbus(err.into()))
+			.and_then(Self::try_from)
+	}
+}
+
+impl From<MprisName> for OwnedBusName {
+	fn from(name: MprisName) -> Self {
+		name.0
+	}
+}
+
+impl Display for MprisName {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}", self.0.as_str())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn split(name: &str) -> (String, Option<String>) {
+		let name = MprisName::from_str(name).unwrap();
+		(
+			name.application().to_string(),
+			name.instance().map(str::to_string),
+		)
+	}
+
+	#[test]
+	fn splits_off_instance_suffixes() {
+		assert_eq!(
+			split("org.mpris.MediaPlayer2.vlc.instance1234"),
+			("vlc".to_string(), Some("instance1234".to_string()))
+		);
+		assert_eq!(
+			split("org.mpris.MediaPlayer2.vlc"),
+			("vlc".to_string(), None)
+		);
+		assert_eq!(
+			split("org.mpris.MediaPlayer2.org.gnome.Music"),
+			("org.gnome.Music".to_string(), None)
+		);
+		assert_eq!(
+			split("org.mpris.MediaPlayer2.firefox.instance_1_84"),
+			("firefox.instance_1_84".to_string(), None)
+		);
+	}
+}
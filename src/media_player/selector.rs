@@ -0,0 +1,82 @@
+// SPDX-License-Identifier: MPL-2.0
+use super::MediaPlayer;
+use crate::{error::Result, player::PlaybackStatus};
+use zbus::Connection;
+
+/// Picks the best player for a command from a priority list,
+/// e.g. "prefer Spotify over Firefox".
+///
+/// Players are ranked by the first preference matching their `DesktopEntry` or `Identity`
+/// (ignoring case), with players matching no preference ranked last.
+/// Ties are broken by playback status: playing, then paused, then stopped.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PlayerSelector {
+	preferred: Vec<String>,
+}
+
+impl PlayerSelector {
+	/// Creates a selector without preferences, which only goes by playback status.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Adds a desktop entry or identity to prefer, after the ones already added.
+	pub fn prefer<S: Into<String>>(mut self, name: S) -> Self {
+		self.preferred.push(name.into());
+		self
+	}
+
+	/// The preferred desktop entries and identities, most preferred first.
+	pub fn preferred(&self) -> &[String] {
+		&self.preferred
+	}
+
+	/// Selects the best of the players available on the given connection.
+	pub async fn select(&self, connection: &Connection) -> Result<Option<MediaPlayer>> {
+		let players = MediaPlayer::new_all_lossy(connection).await?.0;
+		Ok(self.select_from(players).await)
+	}
+
+	/// Selects the best of the given players.
+	///
+	/// Players that don't answer are ranked as if they had no preference and were stopped.
+	pub async fn select_from(&self, players: Vec<MediaPlayer>) -> Option<MediaPlayer> {
+		let mut best: Option<((usize, u8), MediaPlayer)> = None;
+		for media_player in players {
+			let rank = (
+				self.preference(&media_player).await,
+				status_rank(&media_player).await,
+			);
+			if best.as_ref().is_none_or(|(best, _)| rank < *best) {
+				best = Some((rank, media_player));
+			}
+		}
+		best.map(|(_, media_player)| media_player)
+	}
+
+	async fn preference(&self, media_player: &MediaPlayer) -> usize {
+		let desktop_entry = media_player.desktop_entry().await.ok();
+		let identity = media_player.identity().await.ok();
+		self.preferred
+			.iter()
+			.position(|preferred| {
+				[&desktop_entry, &identity]
+					.into_iter()
+					.flatten()
+					.any(|name| name.eq_ignore_ascii_case(preferred))
+			})
+			.unwrap_or(self.preferred.len())
+	}
+}
+
+async fn status_rank(media_player: &MediaPlayer) -> u8 {
+	let status = match media_player.player().await {
+		Ok(player) => player.playback_status().await.ok(),
+		Err(_) => None,
+	};
+	match status {
+		Some(PlaybackStatus::Playing) => 0,
+		Some(PlaybackStatus::Paused) => 1,
+		_ => 2,
+	}
+}
@@ -1,224 +1,469 @@
 // SPDX-License-Identifier: MPL-2.0
-use crate::error::{Error, Result};
+pub mod borrowed;
+pub mod builder;
+mod de;
+pub mod diff;
+mod format;
+#[cfg(feature = "json")]
+pub(crate) mod json;
+pub mod track_metadata;
+pub mod validation;
+
+use self::{builder::MetadataBuilder, diff::MetadataDiff, validation::Violation};
+use crate::{
+	error::{Error, Result},
+	track::{TrackId, NO_TRACK},
+};
+use percent_encoding::{AsciiSet, CONTROLS};
+use serde::{
+	de::{
+		value::MapDeserializer, Deserialize, DeserializeOwned, Deserializer, MapAccess, SeqAccess,
+		Visitor,
+	},
+	ser::{Serialize, Serializer},
+};
 use std::{
 	collections::HashMap,
+	ffi::OsString,
 	fmt,
 	ops::{Deref, DerefMut},
+	os::unix::ffi::{OsStrExt, OsStringExt},
+	path::{Path, PathBuf},
 };
-use time::{Duration, OffsetDateTime};
-use zbus::zvariant::{OwnedObjectPath, Value as ZValue};
+use time::{
+	format_description::well_known::{Iso8601, Rfc3339},
+	Date, Duration, Month, OffsetDateTime, PrimitiveDateTime,
+};
+use zbus::zvariant::{
+	Array, Dict, ObjectPath, OwnedObjectPath, OwnedValue, Signature, Value as ZValue,
+};
+
+/// Fields which the spec types as 32-bit integers.
+const INT32_FIELDS: [&str; 4] = [
+	"xesam:audioBPM",
+	"xesam:discNumber",
+	"xesam:trackNumber",
+	"xesam:useCount",
+];
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(transparent)]
 pub struct Metadata {
 	inner: HashMap<String, MetadataValue>,
+	/// The D-Bus signatures of the fields read off the bus, which [MetadataValue] doesn't keep.
+	#[serde(skip)]
+	signatures: HashMap<String, String>,
 }
 
 impl Metadata {
-	/// `xesam:album`: The track artist(s).
-	pub fn album(&self) -> Option<String> {
+	/// Returns a builder for constructing metadata, e.g. for tests or for emitting it from a player.
+	pub fn builder() -> MetadataBuilder {
+		MetadataBuilder::new()
+	}
+
+	/// Returns the metadata fields by key.
+	pub fn as_map(&self) -> &HashMap<String, MetadataValue> {
+		&self.inner
+	}
+
+	/// Sets `key` to `value`, returning the previous value if there was one.
+	pub fn insert<K: Into<String>, V: Into<MetadataValue>>(
+		&mut self,
+		key: K,
+		value: V,
+	) -> Option<MetadataValue> {
+		let key = key.into();
+		self.signatures.remove(&key);
+		self.inner.insert(key, value.into())
+	}
+
+	/// Removes `key`, returning its value if it was present.
+	pub fn remove(&mut self, key: &str) -> Option<MetadataValue> {
+		self.signatures.remove(key);
+		self.inner.remove(key)
+	}
+
+	/// The D-Bus signature of `key`, e.g. `i` or `o`, as the player sent it.
+	///
+	/// For fields which weren't read off the bus, this is the signature
+	/// inferred by [MetadataValue::signature].
+	pub fn signature(&self, key: &str) -> Option<String> {
+		match self.received_signature(key) {
+			Some(signature) => Some(signature.to_string()),
+			None => self.inner.get(key).map(MetadataValue::signature),
+		}
+	}
+
+	/// The signature `key` was read off the bus with, unless the value has since been
+	/// replaced with one of another type through [DerefMut].
+	pub(crate) fn received_signature(&self, key: &str) -> Option<&str> {
+		let signature = self.signatures.get(key)?;
+		let value = self.inner.get(key)?;
+		(erase_signature(signature) == value.signature()).then_some(signature.as_str())
+	}
+
+	/// The error for reading the field `key` as a `wanted`, with the signature it was sent with.
+	pub(crate) fn field_error(&self, key: &str, wanted: &'static str) -> Error {
+		Error::IncorrectField {
+			key: key.to_string(),
+			wanted,
+			actual: self
+				.inner
+				.get(key)
+				.map_or("Unsupported", MetadataValue::variant),
+			signature: self.signature(key).unwrap_or_default(),
+		}
+	}
+
+	/// Adds a field read off the bus, remembering its signature.
+	pub(crate) fn received(&mut self, key: String, value: &ZValue<'_>) {
+		let mut value = value;
+		while let ZValue::Value(inner) = value {
+			value = inner;
+		}
+		self.signatures
+			.insert(key.clone(), value.value_signature().to_string());
+		self.inner.insert(key, MetadataValue::from(value));
+	}
+
+	/// Iterates over the keys present in the metadata.
+	pub fn keys(&self) -> impl Iterator<Item = &str> {
+		self.inner.keys().map(String::as_str)
+	}
+
+	/// Iterates over the keys outside the `xesam:` and `mpris:` namespaces,
+	/// such as player-specific extras like `spotify:artistUri`.
+	pub fn unknown_keys(&self) -> impl Iterator<Item = &str> {
+		self.keys()
+			.filter(|key| !key.starts_with("xesam:") && !key.starts_with("mpris:"))
+	}
+
+	/// Iterates over the fields whose keys start with `prefix`, e.g. `"spotify:"`.
+	pub fn vendor_fields<'a>(
+		&'a self,
+		prefix: &'a str,
+	) -> impl Iterator<Item = (&'a str, &'a MetadataValue)> + 'a {
 		self.inner
-			.get("xesam:album")
-			.cloned()
-			.and_then(|v| v.try_into_string().ok())
+			.iter()
+			.filter(move |(key, _)| key.starts_with(prefix))
+			.map(|(key, value)| (key.as_str(), value))
+	}
+
+	/// `xesam:album`: The album name.
+	pub fn album(&self) -> Option<String> {
+		self.string("xesam:album")
 	}
 
 	/// `xesam:artist`: The track artist(s).
 	pub fn artists(&self) -> Option<Vec<String>> {
-		self.inner
-			.get("xesam:artist")
-			.cloned()
-			.and_then(|artists| artists.try_into_array().ok())
-			.map(|artists| {
-				artists
-					.into_iter()
-					.filter_map(|v| v.try_into_string().ok())
-					.collect()
-			})
+		self.strings("xesam:artist")
 	}
 
 	/// `xesam:asText`: The track lyrics.
 	pub fn lyrics(&self) -> Option<String> {
-		self.inner
-			.get("xesam:asText")
-			.cloned()
-			.and_then(|v| v.try_into_string().ok())
+		self.string("xesam:asText")
 	}
 
 	/// `xesam:albumArtist`: The album artist(s).
 	pub fn album_artists(&self) -> Option<Vec<String>> {
-		self.inner
-			.get("xesam:albumArtist")
-			.cloned()
-			.and_then(|artists| artists.try_into_array().ok())
-			.map(|artists| {
-				artists
-					.into_iter()
-					.filter_map(|v| v.try_into_string().ok())
-					.collect()
-			})
+		self.strings("xesam:albumArtist")
 	}
 
 	/// `xesam:audioBPM`: The speed of the music, in beats per minute.
+	pub fn audio_bpm(&self) -> Option<u64> {
+		self.uint("xesam:audioBPM")
+	}
+
+	/// `xesam:audioBPM`, failing with [Error::IncorrectField] if it isn't a non-negative integer.
+	pub fn try_audio_bpm(&self) -> Result<Option<u64>> {
+		self.get_as("xesam:audioBPM")
+	}
+
+	/// `xesam:audioBPM`: The speed of the music, in beats per minute.
+	#[deprecated(since = "0.2.0", note = "renamed to `audio_bpm`")]
 	pub fn bpm(&self) -> Option<u64> {
-		self.inner
-			.get("xesam:audioBPM")
-			.cloned()
-			.and_then(|v| v.try_into_uint().ok())
+		self.audio_bpm()
 	}
 
 	/// `xesam:autoRating`: An automatically-generated rating, based on things such as how often it has been played.
 	/// This should be in the range 0.0 to 1.0.
 	pub fn auto_rating(&self) -> Option<f64> {
-		self.inner
-			.get("xesam:autoRating")
-			.cloned()
-			.and_then(|v| v.try_into_double().ok())
+		self.double("xesam:autoRating")
+	}
+
+	/// `xesam:autoRating`, failing with [Error::IncorrectField] if it isn't a number.
+	pub fn try_auto_rating(&self) -> Result<Option<f64>> {
+		self.get_as("xesam:autoRating")
+	}
+
+	/// `xesam:comment`: A (list of) freeform comment(s).
+	pub fn comment(&self) -> Option<Vec<String>> {
+		self.strings("xesam:comment")
 	}
 
 	/// `xesam:composer`: The composer(s) of the track.
 	pub fn composer(&self) -> Option<Vec<String>> {
-		self.inner
-			.get("xesam:composer")
-			.cloned()
-			.and_then(|artists| artists.try_into_array().ok())
-			.map(|artists| {
-				artists
-					.into_iter()
-					.filter_map(|v| v.try_into_string().ok())
-					.collect()
-			})
+		self.strings("xesam:composer")
 	}
 
 	/// `xesam:contentCreated`: When the track was created. Usually only the year component will be useful.
-	pub fn created(&self) -> Option<OffsetDateTime> {
+	pub fn content_created(&self) -> Option<OffsetDateTime> {
 		self.inner
 			.get("xesam:contentCreated")
 			.cloned()
 			.and_then(|v| v.try_into_date().ok())
 	}
 
+	/// `xesam:contentCreated`: When the track was created.
+	#[deprecated(since = "0.2.0", note = "renamed to `content_created`")]
+	pub fn created(&self) -> Option<OffsetDateTime> {
+		self.content_created()
+	}
+
 	/// `xesam:discNumber`: The disc number on the album that this track is from.
 	pub fn disc_number(&self) -> Option<u64> {
-		self.inner
-			.get("xesam:discNumber")
-			.cloned()
-			.and_then(|v| v.try_into_uint().ok())
+		self.uint("xesam:discNumber")
+	}
+
+	/// `xesam:discNumber`, failing with [Error::IncorrectField] if it isn't a non-negative integer.
+	pub fn try_disc_number(&self) -> Result<Option<u64>> {
+		self.get_as("xesam:discNumber")
 	}
 
 	/// `xesam:firstUsed`: When the track was first played.
-	pub fn first_played(&self) -> Option<OffsetDateTime> {
+	pub fn first_used(&self) -> Option<OffsetDateTime> {
 		self.inner
 			.get("xesam:firstUsed")
 			.cloned()
 			.and_then(|v| v.try_into_date().ok())
 	}
 
+	/// `xesam:firstUsed`: When the track was first played.
+	#[deprecated(since = "0.2.0", note = "renamed to `first_used`")]
+	pub fn first_played(&self) -> Option<OffsetDateTime> {
+		self.first_used()
+	}
+
 	/// `xesam:genre`: The genre(s) of the track.
 	pub fn genre(&self) -> Option<Vec<String>> {
-		self.inner
-			.get("xesam:genre")
-			.cloned()
-			.and_then(|artists| artists.try_into_array().ok())
-			.map(|artists| {
-				artists
-					.into_iter()
-					.filter_map(|v| v.try_into_string().ok())
-					.collect()
-			})
+		self.strings("xesam:genre")
 	}
 
 	/// `xesam:lastUsed`: When the track was last played.
-	pub fn last_played(&self) -> Option<OffsetDateTime> {
+	pub fn last_used(&self) -> Option<OffsetDateTime> {
 		self.inner
 			.get("xesam:lastUsed")
 			.cloned()
 			.and_then(|v| v.try_into_date().ok())
 	}
 
+	/// `xesam:lastUsed`: When the track was last played.
+	#[deprecated(since = "0.2.0", note = "renamed to `last_used`")]
+	pub fn last_played(&self) -> Option<OffsetDateTime> {
+		self.last_used()
+	}
+
 	/// `xesam:lyricist`: The lyricist(s) of the track.
 	pub fn lyricist(&self) -> Option<Vec<String>> {
-		self.inner
-			.get("xesam:lyricist")
-			.cloned()
-			.and_then(|artists| artists.try_into_array().ok())
-			.map(|artists| {
-				artists
-					.into_iter()
-					.filter_map(|v| v.try_into_string().ok())
-					.collect()
-			})
+		self.strings("xesam:lyricist")
 	}
 
 	/// `xesam:title`: The track title.
 	pub fn title(&self) -> Option<String> {
-		self.inner
-			.get("xesam:title")
-			.cloned()
-			.and_then(|v| v.try_into_string().ok())
+		self.string("xesam:title")
 	}
 
 	/// `xesam:trackNumber`: The track number on the album that this track is from.
 	pub fn track_number(&self) -> Option<u64> {
-		self.inner
-			.get("xesam:trackNumber")
-			.cloned()
-			.and_then(|v| v.try_into_uint().ok())
+		self.uint("xesam:trackNumber")
+	}
+
+	/// `xesam:trackNumber`, failing with [Error::IncorrectField] if it isn't a non-negative integer.
+	pub fn try_track_number(&self) -> Result<Option<u64>> {
+		self.get_as("xesam:trackNumber")
 	}
 
 	/// `xesam:url`: The location of the media file.
 	pub fn url(&self) -> Option<String> {
-		self.inner
-			.get("xesam:url")
-			.cloned()
-			.and_then(|v| v.try_into_string().ok())
+		self.string("xesam:url")
 	}
 
 	/// `xesam:useCount`: The number of times the track has been played.
 	pub fn use_count(&self) -> Option<u64> {
-		self.inner
-			.get("xesam:useCount")
-			.cloned()
-			.and_then(|v| v.try_into_uint().ok())
+		self.uint("xesam:useCount")
+	}
+
+	/// `xesam:useCount`, failing with [Error::IncorrectField] if it isn't a non-negative integer.
+	pub fn try_use_count(&self) -> Result<Option<u64>> {
+		self.get_as("xesam:useCount")
 	}
 
 	/// `xesam:userRating`: The user's rating of the track.
 	pub fn user_rating(&self) -> Option<f64> {
-		self.inner
-			.get("xesam:userRating")
-			.cloned()
-			.and_then(|v| v.try_into_double().ok())
+		self.double("xesam:userRating")
+	}
+
+	/// `xesam:userRating`, failing with [Error::IncorrectField] if it isn't a number.
+	pub fn try_user_rating(&self) -> Result<Option<f64>> {
+		self.get_as("xesam:userRating")
 	}
 
 	/// `mpris:trackid`: D-Bus path: A unique identity for this track within the context of an MPRIS object (eg: tracklist).
 	pub fn track_id(&self) -> Option<OwnedObjectPath> {
-		self.inner
-			.get("mpris:trackid")
-			.cloned()
-			.and_then(|v| v.try_into_string().ok())
+		self.string("mpris:trackid")
 			.and_then(|path| OwnedObjectPath::try_from(path).ok())
 	}
 
-	/// `mpris:length`: The length of the track in microseconds.
+	/// `mpris:trackid` as a [TrackId], for use with `SetPosition` and track list operations.
+	///
+	/// Returns None if the field is absent or not a valid object path.
+	pub fn track(&self) -> Option<TrackId> {
+		self.track_id().map(TrackId::from)
+	}
+
+	/// `mpris:length`: The length of the track.
+	///
+	/// Players variously send this as a signed or unsigned integer of microseconds,
+	/// and occasionally as a string; negative lengths are treated as absent.
 	pub fn length(&self) -> Option<Duration> {
-		self.inner
-			.get("mpris:length")
-			.cloned()
-			.and_then(|v| match &v {
-				MetadataValue::Int(i) => Some(*i),
-				MetadataValue::UInt(u) => Some(*u as i64),
-				MetadataValue::Str(s) => s.parse().ok(),
-				_ => None,
-			})
-			.map(Duration::microseconds)
+		match self.inner.get("mpris:length")? {
+			MetadataValue::Str(s) => s.trim().parse().ok(),
+			value => value.clone().try_into_int().ok(),
+		}
+		.filter(|length| *length >= 0)
+		.map(Duration::microseconds)
 	}
 
 	/// `mpris:artUrl`: The location of an image representing the track or album.
 	/// Clients should not assume this will continue to exist when the media player stops giving out the URL.
 	pub fn art_url(&self) -> Option<String> {
+		self.string("mpris:artUrl")
+	}
+
+	/// `mpris:artUrl` as a local path, with percent-escapes decoded.
+	///
+	/// Returns None if the field is absent or not a `file://` URL.
+	pub fn art_path(&self) -> Option<PathBuf> {
+		self.art_url().and_then(|url| file_url_to_path(&url))
+	}
+
+	/// `mpris:artUrl`, parsed as a URL.
+	///
+	/// Returns None if the field is absent or not a well-formed URL.
+	#[cfg(feature = "url")]
+	pub fn art_url_parsed(&self) -> Option<url::Url> {
+		self.art_url().and_then(|url| url::Url::parse(&url).ok())
+	}
+
+	/// `xesam:url`, parsed as a URL.
+	///
+	/// Returns None if the field is absent or not a well-formed URL.
+	#[cfg(feature = "url")]
+	pub fn url_parsed(&self) -> Option<url::Url> {
+		self.url().and_then(|url| url::Url::parse(&url).ok())
+	}
+
+	/// Whether both maps describe the same track, ignoring fields such as art or length
+	/// that players often fill in after the track has started.
+	///
+	/// Compares `mpris:trackid` when both sides have one, and `xesam:url` and `xesam:title` otherwise.
+	pub fn same_track(&self, other: &Metadata) -> bool {
+		match (self.string("mpris:trackid"), other.string("mpris:trackid")) {
+			(Some(a), Some(b)) => a == b && a != NO_TRACK,
+			_ => {
+				let (url, title) = (self.url(), self.title());
+				(url.is_some() || title.is_some()) && url == other.url() && title == other.title()
+			}
+		}
+	}
+
+	/// Returns which keys were added, removed or changed going from `self` to `other`,
+	/// e.g. to tell a track change apart from album art arriving late.
+	pub fn diff(&self, other: &Metadata) -> MetadataDiff {
+		MetadataDiff::new(self, other)
+	}
+
+	/// Checks the metadata against the MPRIS metadata guidelines, returning every violation found.
+	///
+	/// This verifies that `mpris:trackid` is present and an object path,
+	/// that known keys hold values of the right type, and that URLs and dates are well-formed.
+	/// For metadata read off the bus, this catches an `mpris:trackid` sent as a string, too.
+	pub fn validate(&self) -> Vec<Violation> {
+		validation::validate(self)
+	}
+
+	/// Renders the metadata into a one-line string, e.g. `"{artist} – {title} ({album})"`.
+	///
+	/// Supported placeholders are `title`, `artist`, `album`, `album_artist`, `composer`, `genre`,
+	/// `lyricist`, `track_number`, `disc_number`, `length` (as `m:ss`), `url`, `art_url` and `track_id`;
+	/// any other placeholder is looked up as a raw key such as `{xesam:comment}`.
+	/// Missing fields render as nothing, and `{{`/`}}` produce literal braces.
+	pub fn format(&self, template: &str) -> String {
+		format::format(self, template)
+	}
+
+	/// Deserializes the metadata into a user-defined type, such as a struct whose fields
+	/// are renamed to the metadata keys they should be read from.
+	pub fn deserialize_into<T: DeserializeOwned>(&self) -> Result<T> {
+		T::deserialize(MapDeserializer::new(self.inner.clone().into_iter()))
+	}
+
+	/// Reads `key` as a `T`, for fields without a dedicated accessor such as vendor-specific keys.
+	///
+	/// Returns `Ok(None)` if the key is absent, and [Error::IncorrectField] if it holds another type.
+	pub fn get_as<T>(&self, key: &str) -> Result<Option<T>>
+	where
+		T: TryFrom<MetadataValue, Error = Error>,
+	{
+		let value = match self.inner.get(key) {
+			Some(value) => value,
+			None => return Ok(None),
+		};
+		T::try_from(value.clone())
+			.map(Some)
+			.map_err(|err| match err {
+				Error::IncorrectVariant { wanted, .. } => self.field_error(key, wanted),
+				err => err,
+			})
+	}
+
+	fn string(&self, key: &str) -> Option<String> {
+		match self.inner.get(key) {
+			Some(MetadataValue::Str(s)) => Some(s.clone()),
+			_ => None,
+		}
+	}
+
+	/// Reads a list of strings, accepting a lone string as a list of one,
+	/// since many players send `s` where the spec requires `as`.
+	fn strings(&self, key: &str) -> Option<Vec<String>> {
+		match self.inner.get(key)? {
+			MetadataValue::Str(s) => Some(vec![s.clone()]),
+			MetadataValue::Array(values) => Some(
+				values
+					.iter()
+					.filter_map(|v| match v {
+						MetadataValue::Str(s) => Some(s.clone()),
+						_ => None,
+					})
+					.collect(),
+			),
+			_ => None,
+		}
+	}
+
+	fn uint(&self, key: &str) -> Option<u64> {
+		self.inner
+			.get(key)
+			.cloned()
+			.and_then(|v| v.try_into_uint().ok())
+	}
+
+	fn double(&self, key: &str) -> Option<f64> {
 		self.inner
-			.get("mpris:artUrl")
+			.get(key)
 			.cloned()
-			.and_then(|v| v.try_into_string().ok())
+			.and_then(|v| v.try_into_double().ok())
 	}
 }
 
@@ -236,6 +481,13 @@ impl DerefMut for Metadata {
 	}
 }
 
+/// Compares the fields, regardless of the signatures they were received with.
+impl PartialEq for Metadata {
+	fn eq(&self, other: &Self) -> bool {
+		self.inner == other.inner
+	}
+}
+
 impl fmt::Display for Metadata {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 		write!(f, "{{")?;
@@ -253,11 +505,81 @@ impl fmt::Display for Metadata {
 
 impl<'a, V: Into<ZValue<'a>>> From<HashMap<String, V>> for Metadata {
 	fn from(map: HashMap<String, V>) -> Self {
+		let mut metadata = Self::default();
+		for (key, value) in map {
+			let value = value.into();
+			metadata.received(key, &value);
+		}
+		metadata
+	}
+}
+
+/// Converts the metadata back into the `a{sv}` map used on the bus,
+/// e.g. to emit it from a player.
+///
+/// `mpris:trackid` is sent as an object path and the spec's 32-bit fields as `i`,
+/// whenever the values allow it; fields holding unsupported values are dropped.
+impl From<Metadata> for HashMap<String, OwnedValue> {
+	fn from(metadata: Metadata) -> Self {
+		metadata
+			.inner
+			.into_iter()
+			.filter_map(|(key, value)| {
+				let value = match (key.as_str(), value) {
+					("mpris:trackid", MetadataValue::Str(path)) => {
+						match ObjectPath::try_from(path.clone()) {
+							Ok(path) => ZValue::ObjectPath(path),
+							Err(_) => ZValue::from(path),
+						}
+					}
+					(key, value) if INT32_FIELDS.contains(&key) => {
+						match value.clone().try_into_int().map(i32::try_from) {
+							Ok(Ok(i)) => ZValue::I32(i),
+							_ => value.into_zvalue()?,
+						}
+					}
+					(_, value) => value.into_zvalue()?,
+				};
+				Some((key, OwnedValue::from(value)))
+			})
+			.collect()
+	}
+}
+
+impl IntoIterator for Metadata {
+	type Item = (String, MetadataValue);
+	type IntoIter = std::collections::hash_map::IntoIter<String, MetadataValue>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.inner.into_iter()
+	}
+}
+
+impl<'a> IntoIterator for &'a Metadata {
+	type Item = (&'a String, &'a MetadataValue);
+	type IntoIter = std::collections::hash_map::Iter<'a, String, MetadataValue>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		self.inner.iter()
+	}
+}
+
+impl<K: Into<String>, V: Into<MetadataValue>> FromIterator<(K, V)> for Metadata {
+	fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
 		Self {
-			inner: map
+			inner: iter
 				.into_iter()
-				.map(|(k, v)| (k, MetadataValue::from(&v.into())))
+				.map(|(k, v)| (k.into(), v.into()))
 				.collect(),
+			signatures: HashMap::new(),
+		}
+	}
+}
+
+impl<K: Into<String>, V: Into<MetadataValue>> Extend<(K, V)> for Metadata {
+	fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+		for (key, value) in iter {
+			self.insert(key, value);
 		}
 	}
 }
@@ -288,6 +610,72 @@ impl MetadataValue {
 		}
 	}
 
+	/// The D-Bus signature the value is sent with, e.g. `s`, `x` or `a{sv}`.
+	///
+	/// Integers of every width are stored as `Int` or `UInt`, so they are reported as `x` or `t`,
+	/// and object paths as `s`; [Metadata::signature] has the signature a player actually sent.
+	/// Arrays whose elements don't share a signature are reported as `av`.
+	pub fn signature(&self) -> String {
+		match self {
+			MetadataValue::Str(_) => "s".to_string(),
+			MetadataValue::Double(_) => "d".to_string(),
+			MetadataValue::Int(_) => "x".to_string(),
+			MetadataValue::UInt(_) => "t".to_string(),
+			MetadataValue::Bool(_) => "b".to_string(),
+			MetadataValue::Array(values) => {
+				let mut signatures = values.iter().map(MetadataValue::signature);
+				match signatures.next() {
+					Some(first) if signatures.all(|signature| signature == first) => {
+						format!("a{}", first)
+					}
+					_ => "av".to_string(),
+				}
+			}
+			MetadataValue::Dict(_) => "a{sv}".to_string(),
+			MetadataValue::__Unsupported => "v".to_string(),
+		}
+	}
+
+	/// Converts the value into a D-Bus value with the signature given by [MetadataValue::signature],
+	/// or None if it is unsupported.
+	fn into_zvalue(self) -> Option<ZValue<'static>> {
+		let signature = self.signature();
+		Some(match self {
+			MetadataValue::Str(s) => ZValue::from(s),
+			MetadataValue::Double(d) => ZValue::F64(d),
+			MetadataValue::Int(i) => ZValue::I64(i),
+			MetadataValue::UInt(u) => ZValue::U64(u),
+			MetadataValue::Bool(b) => ZValue::Bool(b),
+			MetadataValue::Array(values) => {
+				let element = Signature::try_from(signature[1..].to_string()).ok()?;
+				let boxed = element == "v";
+				let mut array = Array::new(element);
+				for value in values.into_iter().filter_map(MetadataValue::into_zvalue) {
+					let value = match boxed {
+						true => ZValue::Value(Box::new(value)),
+						false => value,
+					};
+					array.append(value).ok()?;
+				}
+				ZValue::Array(array)
+			}
+			MetadataValue::Dict(values) => {
+				let mut dict = Dict::new(
+					Signature::from_static_str_unchecked("s"),
+					Signature::from_static_str_unchecked("v"),
+				);
+				for (key, value) in values {
+					if let Some(value) = value.into_zvalue() {
+						dict.append(ZValue::from(key), ZValue::Value(Box::new(value)))
+							.ok()?;
+					}
+				}
+				ZValue::Dict(dict)
+			}
+			MetadataValue::__Unsupported => return None,
+		})
+	}
+
 	/// Tries to extract a string from the variant,
 	/// returning an error if the variant is not a string.
 	pub fn try_into_string(self) -> Result<String> {
@@ -309,17 +697,16 @@ impl MetadataValue {
 
 	/// Tries to extract a date/time from the variant,
 	/// returning an error if the variant is not a date/time.
+	///
+	/// Besides RFC 3339, this accepts the looser ISO 8601 forms players emit in practice:
+	/// a missing offset (assumed UTC), a space instead of `T`, or only a date, year-month or year.
 	pub fn try_into_date(self) -> Result<OffsetDateTime> {
 		let variant = self.variant();
 		match self {
-			MetadataValue::Str(s) => {
-				OffsetDateTime::parse(&s, &time::format_description::well_known::Rfc3339).map_err(
-					|_| Error::IncorrectVariant {
-						wanted: "String (DateTime)",
-						actual: variant,
-					},
-				)
-			}
+			MetadataValue::Str(s) => parse_date(&s).ok_or(Error::IncorrectVariant {
+				wanted: "String (DateTime)",
+				actual: variant,
+			}),
 			_ => Err(Error::IncorrectVariant {
 				wanted: "String (DateTime)",
 				actual: variant,
@@ -335,9 +722,12 @@ impl MetadataValue {
 
 	/// Tries to extract a double from the variant,
 	/// returning an error if the variant is not a double.
+	/// Integers are widened, as some players send ratings as integers.
 	pub fn try_into_double(self) -> Result<f64> {
 		match self {
 			MetadataValue::Double(d) => Ok(d),
+			MetadataValue::Int(i) => Ok(i as f64),
+			MetadataValue::UInt(u) => Ok(u as f64),
 			_ => Err(Error::IncorrectVariant {
 				wanted: "Double",
 				actual: self.variant(),
@@ -354,9 +744,11 @@ impl MetadataValue {
 
 	/// Tries to extract an integer from the variant,
 	/// returning an error if the variant is not an integer.
+	/// Unsigned integers are accepted as long as they fit.
 	pub fn try_into_int(self) -> Result<i64> {
 		match self {
 			MetadataValue::Int(i) => Ok(i),
+			MetadataValue::UInt(u) if u <= i64::MAX as u64 => Ok(u as i64),
 			_ => Err(Error::IncorrectVariant {
 				wanted: "Int",
 				actual: self.variant(),
@@ -372,9 +764,11 @@ impl MetadataValue {
 
 	/// Tries to extract an unsigned integer from the variant,
 	/// returning an error if the variant is not an unsigned integer.
+	/// Signed integers are accepted as long as they are not negative.
 	pub fn try_into_uint(self) -> Result<u64> {
 		match self {
 			MetadataValue::UInt(u) => Ok(u),
+			MetadataValue::Int(i) if i >= 0 => Ok(i as u64),
 			_ => Err(Error::IncorrectVariant {
 				wanted: "UInt",
 				actual: self.variant(),
@@ -446,6 +840,203 @@ impl MetadataValue {
 	}
 }
 
+impl From<String> for MetadataValue {
+	fn from(value: String) -> Self {
+		Self::Str(value)
+	}
+}
+
+impl From<&str> for MetadataValue {
+	fn from(value: &str) -> Self {
+		Self::Str(value.to_string())
+	}
+}
+
+impl From<f64> for MetadataValue {
+	fn from(value: f64) -> Self {
+		Self::Double(value)
+	}
+}
+
+impl From<i64> for MetadataValue {
+	fn from(value: i64) -> Self {
+		Self::Int(value)
+	}
+}
+
+impl From<u64> for MetadataValue {
+	fn from(value: u64) -> Self {
+		Self::UInt(value)
+	}
+}
+
+impl From<bool> for MetadataValue {
+	fn from(value: bool) -> Self {
+		Self::Bool(value)
+	}
+}
+
+impl<T: Into<MetadataValue>> From<Vec<T>> for MetadataValue {
+	fn from(values: Vec<T>) -> Self {
+		Self::Array(values.into_iter().map(Into::into).collect())
+	}
+}
+
+impl From<HashMap<String, MetadataValue>> for MetadataValue {
+	fn from(values: HashMap<String, MetadataValue>) -> Self {
+		Self::Dict(values)
+	}
+}
+
+impl TryFrom<MetadataValue> for String {
+	type Error = Error;
+
+	fn try_from(value: MetadataValue) -> Result<Self> {
+		value.try_into_string()
+	}
+}
+
+impl TryFrom<MetadataValue> for OffsetDateTime {
+	type Error = Error;
+
+	fn try_from(value: MetadataValue) -> Result<Self> {
+		value.try_into_date()
+	}
+}
+
+impl TryFrom<MetadataValue> for f64 {
+	type Error = Error;
+
+	fn try_from(value: MetadataValue) -> Result<Self> {
+		value.try_into_double()
+	}
+}
+
+impl TryFrom<MetadataValue> for i64 {
+	type Error = Error;
+
+	fn try_from(value: MetadataValue) -> Result<Self> {
+		value.try_into_int()
+	}
+}
+
+impl TryFrom<MetadataValue> for u64 {
+	type Error = Error;
+
+	fn try_from(value: MetadataValue) -> Result<Self> {
+		value.try_into_uint()
+	}
+}
+
+impl TryFrom<MetadataValue> for bool {
+	type Error = Error;
+
+	fn try_from(value: MetadataValue) -> Result<Self> {
+		value.try_into_bool()
+	}
+}
+
+impl TryFrom<MetadataValue> for Vec<MetadataValue> {
+	type Error = Error;
+
+	fn try_from(value: MetadataValue) -> Result<Self> {
+		value.try_into_array()
+	}
+}
+
+impl TryFrom<MetadataValue> for HashMap<String, MetadataValue> {
+	type Error = Error;
+
+	fn try_from(value: MetadataValue) -> Result<Self> {
+		value.try_into_dict()
+	}
+}
+
+impl TryFrom<MetadataValue> for Vec<String> {
+	type Error = Error;
+
+	fn try_from(value: MetadataValue) -> Result<Self> {
+		value
+			.try_into_array()?
+			.into_iter()
+			.map(MetadataValue::try_into_string)
+			.collect()
+	}
+}
+
+/// Characters escaped when turning a local path into a `file://` URL.
+const PATH: &AsciiSet = &CONTROLS
+	.add(b' ')
+	.add(b'"')
+	.add(b'#')
+	.add(b'%')
+	.add(b'<')
+	.add(b'>')
+	.add(b'?')
+	.add(b'`')
+	.add(b'{')
+	.add(b'}');
+
+/// Converts an absolute path into a `file://` URL, escaping characters which aren't allowed in URLs.
+pub(crate) fn path_to_file_url(path: &Path) -> String {
+	let bytes = path.as_os_str().as_bytes();
+	format!("file://{}", percent_encoding::percent_encode(bytes, PATH))
+}
+
+/// Converts a `file://` URL into a path, decoding percent-escapes.
+pub(crate) fn file_url_to_path(url: &str) -> Option<PathBuf> {
+	let rest = url
+		.get(.."file://".len())
+		.filter(|scheme| scheme.eq_ignore_ascii_case("file://"))
+		.map(|_| &url["file://".len()..])?;
+	// Skip the authority, which is usually empty or `localhost`.
+	let path = &rest[rest.find('/')?..];
+	let path = path.split(['?', '#']).next().unwrap_or(path);
+	let bytes = percent_encoding::percent_decode_str(path).collect::<Vec<u8>>();
+	Some(PathBuf::from(OsString::from_vec(bytes)))
+}
+
+/// Maps a received signature to the one of the [MetadataValue] it converts into,
+/// e.g. `ai` to `ax` and `o` to `s`.
+fn erase_signature(signature: &str) -> String {
+	signature
+		.chars()
+		.map(|c| match c {
+			'n' | 'i' => 'x',
+			'y' | 'q' | 'u' => 't',
+			'o' => 's',
+			c => c,
+		})
+		.collect()
+}
+
+fn parse_date(s: &str) -> Option<OffsetDateTime> {
+	let s = s.trim();
+	if let Ok(date) = OffsetDateTime::parse(s, &Rfc3339) {
+		return Some(date);
+	}
+	let s = s.replacen(' ', "T", 1);
+	if let Ok(date) = OffsetDateTime::parse(&s, &Iso8601::PARSING) {
+		return Some(date);
+	}
+	if let Ok(date) = PrimitiveDateTime::parse(&s, &Iso8601::PARSING) {
+		return Some(date.assume_utc());
+	}
+	if let Ok(date) = Date::parse(&s, &Iso8601::PARSING) {
+		return Some(date.midnight().assume_utc());
+	}
+	// Year-month and bare years, as commonly found in `xesam:contentCreated`.
+	let mut parts = s.splitn(2, '-');
+	let year = parts.next()?.parse::<i32>().ok()?;
+	let month = match parts.next() {
+		Some(month) => Month::try_from(month.parse::<u8>().ok()?).ok()?,
+		None => Month::January,
+	};
+	Date::from_calendar_date(year, month, 1)
+		.ok()
+		.map(|date| date.midnight().assume_utc())
+}
+
 impl<'a> From<&ZValue<'a>> for MetadataValue {
 	fn from(value: &ZValue) -> Self {
 		match value {
@@ -474,6 +1065,109 @@ impl<'a> From<&ZValue<'a>> for MetadataValue {
 	}
 }
 
+impl Serialize for MetadataValue {
+	fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		match self {
+			Self::Str(s) => serializer.serialize_str(s),
+			Self::Double(d) => serializer.serialize_f64(*d),
+			Self::Int(i) => serializer.serialize_i64(*i),
+			Self::UInt(u) => serializer.serialize_u64(*u),
+			Self::Bool(b) => serializer.serialize_bool(*b),
+			Self::Array(a) => serializer.collect_seq(a),
+			Self::Dict(d) => serializer.collect_map(d),
+			Self::__Unsupported => serializer.serialize_unit(),
+		}
+	}
+}
+
+impl<'de> Deserialize<'de> for MetadataValue {
+	fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		deserializer.deserialize_any(MetadataValueVisitor)
+	}
+}
+
+struct MetadataValueVisitor;
+
+impl<'de> Visitor<'de> for MetadataValueVisitor {
+	type Value = MetadataValue;
+
+	fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+		formatter.write_str("a string, number, boolean, list or map")
+	}
+
+	fn visit_bool<E>(self, b: bool) -> std::result::Result<Self::Value, E> {
+		Ok(MetadataValue::Bool(b))
+	}
+
+	fn visit_i64<E>(self, i: i64) -> std::result::Result<Self::Value, E> {
+		Ok(MetadataValue::Int(i))
+	}
+
+	fn visit_u64<E>(self, u: u64) -> std::result::Result<Self::Value, E> {
+		Ok(MetadataValue::UInt(u))
+	}
+
+	fn visit_f64<E>(self, d: f64) -> std::result::Result<Self::Value, E> {
+		Ok(MetadataValue::Double(d))
+	}
+
+	fn visit_str<E>(self, s: &str) -> std::result::Result<Self::Value, E> {
+		Ok(MetadataValue::Str(s.to_string()))
+	}
+
+	fn visit_string<E>(self, s: String) -> std::result::Result<Self::Value, E> {
+		Ok(MetadataValue::Str(s))
+	}
+
+	fn visit_unit<E>(self) -> std::result::Result<Self::Value, E> {
+		Ok(MetadataValue::__Unsupported)
+	}
+
+	fn visit_none<E>(self) -> std::result::Result<Self::Value, E> {
+		Ok(MetadataValue::__Unsupported)
+	}
+
+	fn visit_seq<A>(self, mut seq: A) -> std::result::Result<Self::Value, A::Error>
+	where
+		A: SeqAccess<'de>,
+	{
+		let mut values = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+		while let Some(value) = seq.next_element()? {
+			values.push(value);
+		}
+		Ok(MetadataValue::Array(values))
+	}
+
+	fn visit_map<A>(self, mut map: A) -> std::result::Result<Self::Value, A::Error>
+	where
+		A: MapAccess<'de>,
+	{
+		let mut values = HashMap::with_capacity(map.size_hint().unwrap_or(0));
+		while let Some((key, value)) = map.next_entry()? {
+			values.insert(key, value);
+		}
+		Ok(MetadataValue::Dict(values))
+	}
+}
+
+impl<'a> From<ZValue<'a>> for MetadataValue {
+	fn from(value: ZValue<'a>) -> Self {
+		Self::from(&value)
+	}
+}
+
+impl From<OwnedValue> for MetadataValue {
+	fn from(value: OwnedValue) -> Self {
+		Self::from(&*value)
+	}
+}
+
 impl fmt::Debug for MetadataValue {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 		match self {
@@ -531,3 +1225,105 @@ impl fmt::Display for MetadataValue {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_partial_dates() {
+		let date = |s| parse_date(s).map(|date| date.date());
+		let expected = Date::from_calendar_date(2007, Month::April, 1).unwrap();
+		assert_eq!(date("2007-04-01T12:30:00+02:00"), Some(expected));
+		assert_eq!(date("2007-04-01 12:30:00"), Some(expected));
+		assert_eq!(date("2007-04-01"), Some(expected));
+		assert_eq!(date("2007-04"), Some(expected));
+		assert_eq!(
+			date("2007"),
+			Some(Date::from_calendar_date(2007, Month::January, 1).unwrap())
+		);
+		assert_eq!(date("2007-13"), None);
+		assert_eq!(date("yesterday"), None);
+	}
+
+	#[test]
+	fn file_urls_round_trip() {
+		let path = Path::new("/music/AC DC/100% #1?.flac");
+		let url = path_to_file_url(path);
+		assert_eq!(url, "file:///music/AC%20DC/100%25%20%231%3F.flac");
+		assert_eq!(file_url_to_path(&url).as_deref(), Some(path));
+		assert_eq!(
+			file_url_to_path("file://localhost/a%20b").as_deref(),
+			Some(Path::new("/a b"))
+		);
+		assert_eq!(file_url_to_path("https://example.com/a"), None);
+	}
+
+	#[test]
+	fn converts_to_bus_types() {
+		let metadata = Metadata::builder()
+			.insert("mpris:trackid", "/org/mpris/MediaPlayer2/Track/1")
+			.track_number(3)
+			.artists(["A", "B"])
+			.insert("xesam:useCount", i64::MAX)
+			.build();
+		let map = HashMap::<String, OwnedValue>::from(metadata.clone());
+		let signature = |key: &str| map[key].value_signature().to_string();
+		assert_eq!(signature("mpris:trackid"), "o");
+		assert_eq!(signature("xesam:trackNumber"), "i");
+		assert_eq!(signature("xesam:artist"), "as");
+		// Too large for `i`, so it keeps its own type.
+		assert_eq!(signature("xesam:useCount"), "x");
+		assert_eq!(Metadata::from(map), metadata);
+	}
+
+	#[test]
+	fn records_received_signatures() {
+		let mut map = HashMap::new();
+		map.insert(
+			"mpris:trackid".to_string(),
+			ZValue::from("/org/mpris/MediaPlayer2/Track/1"),
+		);
+		map.insert("xesam:trackNumber".to_string(), ZValue::U32(3));
+		let mut metadata = Metadata::from(map);
+		assert_eq!(metadata.signature("mpris:trackid").as_deref(), Some("s"));
+		assert_eq!(
+			metadata.signature("xesam:trackNumber").as_deref(),
+			Some("u")
+		);
+		assert_eq!(
+			metadata.validate(),
+			[Violation::StringTrackId(
+				"/org/mpris/MediaPlayer2/Track/1".to_string()
+			)]
+		);
+		metadata.insert("xesam:trackNumber", 3i64);
+		assert_eq!(
+			metadata.signature("xesam:trackNumber").as_deref(),
+			Some("x")
+		);
+	}
+
+	#[cfg(feature = "json")]
+	#[test]
+	fn json_round_trips() {
+		let metadata = Metadata::builder()
+			.title("Title")
+			.length(Duration::seconds(200))
+			.user_rating(0.5)
+			.artists(["A"])
+			.insert("xesam:useCount", u64::MAX)
+			.build();
+		assert_eq!(Metadata::from_json(&metadata.to_json()).unwrap(), metadata);
+		assert!(Metadata::from_json("[]").is_err());
+
+		let mut map = HashMap::new();
+		map.insert("xesam:trackNumber".to_string(), ZValue::I32(3));
+		map.insert("xesam:autoRating".to_string(), ZValue::F64(f64::INFINITY));
+		map.insert("xesam:userRating".to_string(), ZValue::F64(f64::NAN));
+		let decoded = Metadata::from_json(&Metadata::from(map).to_json()).unwrap();
+		assert_eq!(decoded.signature("xesam:trackNumber").as_deref(), Some("i"));
+		assert_eq!(decoded.auto_rating(), Some(f64::INFINITY));
+		assert!(decoded.user_rating().unwrap().is_nan());
+	}
+}
@@ -0,0 +1,120 @@
+// SPDX-License-Identifier: MPL-2.0
+use super::Metadata;
+use std::collections::HashMap;
+use time::Duration;
+use zbus::zvariant::{OwnedValue, Value as ZValue};
+
+/// A zero-copy view over a raw metadata map, as returned by the proxies.
+///
+/// Accessors borrow from the underlying map instead of converting every value,
+/// which avoids deep clones when only a few fields of a large track list are needed.
+#[derive(Debug, Clone, Copy)]
+pub struct MetadataRef<'a> {
+	inner: &'a HashMap<String, OwnedValue>,
+}
+
+impl<'a> MetadataRef<'a> {
+	/// Wraps a raw metadata map.
+	pub fn new(inner: &'a HashMap<String, OwnedValue>) -> Self {
+		Self { inner }
+	}
+
+	/// Returns the raw value of `key`, with any nested variants unwrapped.
+	pub fn get(&self, key: &str) -> Option<&'a ZValue<'a>> {
+		let mut value: &'a ZValue<'a> = self.inner.get(key)?;
+		while let ZValue::Value(inner) = value {
+			value = inner;
+		}
+		Some(value)
+	}
+
+	/// `xesam:album`: The album name.
+	pub fn album(&self) -> Option<&'a str> {
+		self.str("xesam:album")
+	}
+
+	/// `xesam:artist`: The track artist(s).
+	pub fn artists(&self) -> Option<Vec<&'a str>> {
+		self.strs("xesam:artist")
+	}
+
+	/// `xesam:albumArtist`: The album artist(s).
+	pub fn album_artists(&self) -> Option<Vec<&'a str>> {
+		self.strs("xesam:albumArtist")
+	}
+
+	/// `xesam:title`: The track title.
+	pub fn title(&self) -> Option<&'a str> {
+		self.str("xesam:title")
+	}
+
+	/// `xesam:url`: The location of the media file.
+	pub fn url(&self) -> Option<&'a str> {
+		self.str("xesam:url")
+	}
+
+	/// `mpris:artUrl`: The location of an image representing the track or album.
+	pub fn art_url(&self) -> Option<&'a str> {
+		self.str("mpris:artUrl")
+	}
+
+	/// `mpris:trackid`: A unique identity for this track within the context of an MPRIS object.
+	pub fn track_id(&self) -> Option<&'a str> {
+		match self.get("mpris:trackid")? {
+			ZValue::ObjectPath(path) => Some(path.as_str()),
+			ZValue::Str(s) => Some(s.as_str()),
+			_ => None,
+		}
+	}
+
+	/// `mpris:length`: The length of the track.
+	pub fn length(&self) -> Option<Duration> {
+		let length = match self.get("mpris:length")? {
+			ZValue::I64(i) => *i,
+			ZValue::U64(u) => i64::try_from(*u).ok()?,
+			ZValue::I32(i) => *i as i64,
+			ZValue::U32(u) => *u as i64,
+			_ => return None,
+		};
+		(length >= 0).then(|| Duration::microseconds(length))
+	}
+
+	/// Converts the view into an owned [Metadata].
+	pub fn to_metadata(&self) -> Metadata {
+		let mut metadata = Metadata::default();
+		for (key, value) in self.inner {
+			metadata.received(key.clone(), value);
+		}
+		metadata
+	}
+
+	fn str(&self, key: &str) -> Option<&'a str> {
+		match self.get(key)? {
+			ZValue::Str(s) => Some(s.as_str()),
+			_ => None,
+		}
+	}
+
+	fn strs(&self, key: &str) -> Option<Vec<&'a str>> {
+		match self.get(key)? {
+			ZValue::Str(s) => Some(vec![s.as_str()]),
+			ZValue::Array(values) => Some(
+				values
+					.get()
+					.iter()
+					.filter_map(|v| match v {
+						ZValue::Str(s) => Some(s.as_str()),
+						_ => None,
+					})
+					.collect(),
+			),
+			_ => None,
+		}
+	}
+}
+
+impl<'a> From<&'a HashMap<String, OwnedValue>> for MetadataRef<'a> {
+	fn from(inner: &'a HashMap<String, OwnedValue>) -> Self {
+		Self::new(inner)
+	}
+}
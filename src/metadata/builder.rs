@@ -0,0 +1,158 @@
+// SPDX-License-Identifier: MPL-2.0
+use super::{Metadata, MetadataValue};
+use std::collections::HashMap;
+use time::{format_description::well_known::Rfc3339, Duration, OffsetDateTime};
+use zbus::zvariant::OwnedObjectPath;
+
+/// Builds a [Metadata] map using the value types required by the MPRIS specification.
+#[derive(Debug, Clone, Default)]
+pub struct MetadataBuilder {
+	inner: HashMap<String, MetadataValue>,
+}
+
+impl MetadataBuilder {
+	/// Creates an empty builder.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Sets an arbitrary field, such as a vendor-specific key.
+	pub fn insert<K: Into<String>, V: Into<MetadataValue>>(mut self, key: K, value: V) -> Self {
+		self.inner.insert(key.into(), value.into());
+		self
+	}
+
+	/// `mpris:trackid`: A unique identity for this track within the context of an MPRIS object.
+	pub fn track_id(self, track_id: OwnedObjectPath) -> Self {
+		self.insert("mpris:trackid", track_id.as_str())
+	}
+
+	/// `mpris:length`: The length of the track.
+	pub fn length(self, length: Duration) -> Self {
+		self.insert("mpris:length", length.whole_microseconds() as i64)
+	}
+
+	/// `mpris:artUrl`: The location of an image representing the track or album.
+	pub fn art_url<S: Into<String>>(self, art_url: S) -> Self {
+		self.insert("mpris:artUrl", art_url.into())
+	}
+
+	/// `xesam:album`: The album name.
+	pub fn album<S: Into<String>>(self, album: S) -> Self {
+		self.insert("xesam:album", album.into())
+	}
+
+	/// `xesam:albumArtist`: The album artist(s).
+	pub fn album_artists<I: IntoIterator<Item = S>, S: Into<String>>(self, artists: I) -> Self {
+		self.insert("xesam:albumArtist", strings(artists))
+	}
+
+	/// `xesam:artist`: The track artist(s).
+	pub fn artists<I: IntoIterator<Item = S>, S: Into<String>>(self, artists: I) -> Self {
+		self.insert("xesam:artist", strings(artists))
+	}
+
+	/// `xesam:asText`: The track lyrics.
+	pub fn lyrics<S: Into<String>>(self, lyrics: S) -> Self {
+		self.insert("xesam:asText", lyrics.into())
+	}
+
+	/// `xesam:audioBPM`: The speed of the music, in beats per minute.
+	pub fn audio_bpm(self, bpm: i32) -> Self {
+		self.insert("xesam:audioBPM", bpm as i64)
+	}
+
+	/// `xesam:autoRating`: An automatically-generated rating, in the range 0.0 to 1.0.
+	pub fn auto_rating(self, rating: f64) -> Self {
+		self.insert("xesam:autoRating", rating)
+	}
+
+	/// `xesam:comment`: A (list of) freeform comment(s).
+	pub fn comment<I: IntoIterator<Item = S>, S: Into<String>>(self, comments: I) -> Self {
+		self.insert("xesam:comment", strings(comments))
+	}
+
+	/// `xesam:composer`: The composer(s) of the track.
+	pub fn composer<I: IntoIterator<Item = S>, S: Into<String>>(self, composers: I) -> Self {
+		self.insert("xesam:composer", strings(composers))
+	}
+
+	/// `xesam:contentCreated`: When the track was created.
+	pub fn content_created(self, date: OffsetDateTime) -> Self {
+		self.date("xesam:contentCreated", date)
+	}
+
+	/// `xesam:discNumber`: The disc number on the album that this track is from.
+	pub fn disc_number(self, disc_number: i32) -> Self {
+		self.insert("xesam:discNumber", disc_number as i64)
+	}
+
+	/// `xesam:firstUsed`: When the track was first played.
+	pub fn first_used(self, date: OffsetDateTime) -> Self {
+		self.date("xesam:firstUsed", date)
+	}
+
+	/// `xesam:genre`: The genre(s) of the track.
+	pub fn genre<I: IntoIterator<Item = S>, S: Into<String>>(self, genres: I) -> Self {
+		self.insert("xesam:genre", strings(genres))
+	}
+
+	/// `xesam:lastUsed`: When the track was last played.
+	pub fn last_used(self, date: OffsetDateTime) -> Self {
+		self.date("xesam:lastUsed", date)
+	}
+
+	/// `xesam:lyricist`: The lyricist(s) of the track.
+	pub fn lyricist<I: IntoIterator<Item = S>, S: Into<String>>(self, lyricists: I) -> Self {
+		self.insert("xesam:lyricist", strings(lyricists))
+	}
+
+	/// `xesam:title`: The track title.
+	pub fn title<S: Into<String>>(self, title: S) -> Self {
+		self.insert("xesam:title", title.into())
+	}
+
+	/// `xesam:trackNumber`: The track number on the album that this track is from.
+	pub fn track_number(self, track_number: i32) -> Self {
+		self.insert("xesam:trackNumber", track_number as i64)
+	}
+
+	/// `xesam:url`: The location of the media file.
+	pub fn url<S: Into<String>>(self, url: S) -> Self {
+		self.insert("xesam:url", url.into())
+	}
+
+	/// `xesam:useCount`: The number of times the track has been played.
+	pub fn use_count(self, use_count: i32) -> Self {
+		self.insert("xesam:useCount", use_count as i64)
+	}
+
+	/// `xesam:userRating`: The user's rating of the track, in the range 0.0 to 1.0.
+	pub fn user_rating(self, rating: f64) -> Self {
+		self.insert("xesam:userRating", rating)
+	}
+
+	/// Finishes building the metadata.
+	pub fn build(self) -> Metadata {
+		Metadata {
+			inner: self.inner,
+			signatures: HashMap::new(),
+		}
+	}
+
+	fn date(self, key: &str, date: OffsetDateTime) -> Self {
+		match date.format(&Rfc3339) {
+			Ok(date) => self.insert(key, date),
+			Err(_) => self,
+		}
+	}
+}
+
+fn strings<I: IntoIterator<Item = S>, S: Into<String>>(values: I) -> MetadataValue {
+	MetadataValue::Array(
+		values
+			.into_iter()
+			.map(|value| MetadataValue::Str(value.into()))
+			.collect(),
+	)
+}
@@ -0,0 +1,85 @@
+// SPDX-License-Identifier: MPL-2.0
+use super::MetadataValue;
+use crate::error::{Error, Result};
+use serde::de::{
+	value::{MapDeserializer, SeqDeserializer},
+	Deserializer, IntoDeserializer, Visitor,
+};
+
+impl<'de> Deserializer<'de> for MetadataValue {
+	type Error = Error;
+
+	fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+	where
+		V: Visitor<'de>,
+	{
+		match self {
+			MetadataValue::Str(s) => visitor.visit_string(s),
+			MetadataValue::Double(d) => visitor.visit_f64(d),
+			MetadataValue::Int(i) => visitor.visit_i64(i),
+			MetadataValue::UInt(u) => visitor.visit_u64(u),
+			MetadataValue::Bool(b) => visitor.visit_bool(b),
+			MetadataValue::Array(a) => visitor.visit_seq(SeqDeserializer::new(a.into_iter())),
+			MetadataValue::Dict(d) => visitor.visit_map(MapDeserializer::new(d.into_iter())),
+			MetadataValue::__Unsupported => visitor.visit_unit(),
+		}
+	}
+
+	fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+	where
+		V: Visitor<'de>,
+	{
+		match self {
+			MetadataValue::__Unsupported => visitor.visit_none(),
+			value => visitor.visit_some(value),
+		}
+	}
+
+	fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value>
+	where
+		V: Visitor<'de>,
+	{
+		// Many players send a lone string where the spec requires a list of strings.
+		match self {
+			MetadataValue::Str(s) => {
+				visitor.visit_seq(SeqDeserializer::new(std::iter::once(MetadataValue::Str(s))))
+			}
+			value => value.deserialize_any(visitor),
+		}
+	}
+
+	fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+	where
+		V: Visitor<'de>,
+	{
+		visitor.visit_newtype_struct(self)
+	}
+
+	fn deserialize_enum<V>(
+		self,
+		_name: &'static str,
+		_variants: &'static [&'static str],
+		visitor: V,
+	) -> Result<V::Value>
+	where
+		V: Visitor<'de>,
+	{
+		match self {
+			MetadataValue::Str(s) => visitor.visit_enum(s.into_deserializer()),
+			value => value.deserialize_any(visitor),
+		}
+	}
+
+	serde::forward_to_deserialize_any! {
+		bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+		bytes byte_buf unit unit_struct tuple tuple_struct map struct identifier ignored_any
+	}
+}
+
+impl<'de> IntoDeserializer<'de, Error> for MetadataValue {
+	type Deserializer = Self;
+
+	fn into_deserializer(self) -> Self::Deserializer {
+		self
+	}
+}
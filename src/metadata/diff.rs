@@ -0,0 +1,49 @@
+// SPDX-License-Identifier: MPL-2.0
+use super::Metadata;
+
+/// The keys that differ between two [Metadata] maps, as returned by [Metadata::diff].
+///
+/// Each list is sorted, so diffs can be compared and printed deterministically.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MetadataDiff {
+	/// Keys only present in the newer metadata.
+	pub added: Vec<String>,
+	/// Keys only present in the older metadata.
+	pub removed: Vec<String>,
+	/// Keys present in both, but with different values.
+	pub changed: Vec<String>,
+}
+
+impl MetadataDiff {
+	pub(super) fn new(old: &Metadata, new: &Metadata) -> Self {
+		let mut diff = Self::default();
+		for (key, value) in &new.inner {
+			match old.inner.get(key) {
+				None => diff.added.push(key.clone()),
+				Some(old_value) if old_value != value => diff.changed.push(key.clone()),
+				Some(_) => {}
+			}
+		}
+		for key in old.inner.keys() {
+			if !new.inner.contains_key(key) {
+				diff.removed.push(key.clone());
+			}
+		}
+		diff.added.sort();
+		diff.removed.sort();
+		diff.changed.sort();
+		diff
+	}
+
+	/// Whether both maps were identical.
+	pub fn is_empty(&self) -> bool {
+		self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+	}
+
+	/// Whether `key` was added, removed or changed.
+	pub fn contains(&self, key: &str) -> bool {
+		[&self.added, &self.removed, &self.changed]
+			.iter()
+			.any(|keys| keys.iter().any(|k| k == key))
+	}
+}
@@ -0,0 +1,86 @@
+// SPDX-License-Identifier: MPL-2.0
+use super::{Metadata, MetadataValue};
+use std::fmt::Write;
+
+pub(super) fn format(metadata: &Metadata, template: &str) -> String {
+	let mut output = String::with_capacity(template.len());
+	let mut chars = template.chars().peekable();
+	while let Some(c) = chars.next() {
+		match c {
+			'{' if chars.peek() == Some(&'{') => {
+				chars.next();
+				output.push('{');
+			}
+			'}' if chars.peek() == Some(&'}') => {
+				chars.next();
+				output.push('}');
+			}
+			'{' => {
+				let mut name = String::new();
+				for c in chars.by_ref() {
+					if c == '}' {
+						break;
+					}
+					name.push(c);
+				}
+				output.push_str(&field(metadata, name.trim()));
+			}
+			c => output.push(c),
+		}
+	}
+	output
+}
+
+fn field(metadata: &Metadata, name: &str) -> String {
+	let number = |n: Option<u64>| n.map(|n| n.to_string()).unwrap_or_default();
+	let list = |values: Option<Vec<String>>| values.unwrap_or_default().join(", ");
+	match name {
+		"title" => metadata.title().unwrap_or_default(),
+		"artist" | "artists" => list(metadata.artists()),
+		"album" => metadata.album().unwrap_or_default(),
+		"album_artist" | "album_artists" => list(metadata.album_artists()),
+		"composer" => list(metadata.composer()),
+		"genre" => list(metadata.genre()),
+		"lyricist" => list(metadata.lyricist()),
+		"track_number" => number(metadata.track_number()),
+		"disc_number" => number(metadata.disc_number()),
+		"length" => metadata
+			.length()
+			.map(|length| {
+				let seconds = length.whole_seconds();
+				if seconds >= 3600 {
+					format!(
+						"{}:{:02}:{:02}",
+						seconds / 3600,
+						seconds / 60 % 60,
+						seconds % 60
+					)
+				} else {
+					format!("{}:{:02}", seconds / 60, seconds % 60)
+				}
+			})
+			.unwrap_or_default(),
+		"url" => metadata.url().unwrap_or_default(),
+		"art_url" => metadata.art_url().unwrap_or_default(),
+		"track_id" => metadata.string("mpris:trackid").unwrap_or_default(),
+		key => metadata.inner.get(key).map(plain).unwrap_or_default(),
+	}
+}
+
+/// Renders a raw value without the quoting used by its `Display` implementation.
+fn plain(value: &MetadataValue) -> String {
+	match value {
+		MetadataValue::Str(s) => s.clone(),
+		MetadataValue::Array(values) => {
+			let mut output = String::new();
+			for (i, value) in values.iter().enumerate() {
+				if i > 0 {
+					output.push_str(", ");
+				}
+				let _ = write!(output, "{}", plain(value));
+			}
+			output
+		}
+		value => value.to_string(),
+	}
+}
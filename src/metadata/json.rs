@@ -0,0 +1,108 @@
+// SPDX-License-Identifier: MPL-2.0
+use super::{Metadata, MetadataValue};
+use crate::error::{Error, Result};
+use serde_json::{json, Map, Value as JsonValue};
+
+impl Metadata {
+	/// Encodes the metadata as JSON, tagging every value with its type
+	/// (e.g. `{"xesam:trackNumber": {"type": "Int", "value": 3, "signature": "i"}}`) so that
+	/// [Metadata::from_json] can restore it exactly, including the D-Bus signature
+	/// of fields read off the bus.
+	pub fn to_json(&self) -> String {
+		let map = self
+			.inner
+			.iter()
+			.map(|(key, value)| {
+				let mut encoded = encode(value);
+				if let Some(signature) = self.received_signature(key) {
+					encoded["signature"] = json!(signature);
+				}
+				(key.clone(), encoded)
+			})
+			.collect::<Map<String, JsonValue>>();
+		JsonValue::Object(map).to_string()
+	}
+
+	/// Decodes metadata produced by [Metadata::to_json].
+	pub fn from_json(json: &str) -> Result<Self> {
+		let value = serde_json::from_str::<JsonValue>(json).map_err(invalid)?;
+		let JsonValue::Object(map) = value else {
+			return Err(invalid("expected an object"));
+		};
+		let mut metadata = Metadata::default();
+		for (key, value) in map {
+			let signature = value.get("signature").and_then(JsonValue::as_str);
+			if let Some(signature) = signature {
+				metadata
+					.signatures
+					.insert(key.clone(), signature.to_string());
+			}
+			metadata.inner.insert(key, decode(&value)?);
+		}
+		Ok(metadata)
+	}
+}
+
+pub(crate) fn encode(value: &MetadataValue) -> JsonValue {
+	let encoded = match value {
+		MetadataValue::Str(s) => json!(s),
+		// JSON has no representation for these, so they are spelled out as strings.
+		MetadataValue::Double(d) if d.is_nan() => json!("NaN"),
+		MetadataValue::Double(d) if d.is_infinite() => json!(if *d > 0.0 { "inf" } else { "-inf" }),
+		MetadataValue::Double(d) => json!(d),
+		MetadataValue::Int(i) => json!(i),
+		MetadataValue::UInt(u) => json!(u),
+		MetadataValue::Bool(b) => json!(b),
+		MetadataValue::Array(a) => JsonValue::Array(a.iter().map(encode).collect()),
+		MetadataValue::Dict(d) => JsonValue::Object(
+			d.iter()
+				.map(|(key, value)| (key.clone(), encode(value)))
+				.collect(),
+		),
+		MetadataValue::__Unsupported => JsonValue::Null,
+	};
+	json!({ "type": value.variant(), "value": encoded })
+}
+
+pub(crate) fn decode(value: &JsonValue) -> Result<MetadataValue> {
+	let ty = value
+		.get("type")
+		.and_then(JsonValue::as_str)
+		.ok_or_else(|| invalid("missing value type"))?;
+	let value = value.get("value").ok_or_else(|| invalid("missing value"))?;
+	let mismatch = || invalid(format!("value does not match its type {}", ty));
+	Ok(match ty {
+		"Str" => MetadataValue::Str(value.as_str().ok_or_else(mismatch)?.to_string()),
+		"Double" => MetadataValue::Double(match value.as_str() {
+			Some("NaN") => f64::NAN,
+			Some("inf") => f64::INFINITY,
+			Some("-inf") => f64::NEG_INFINITY,
+			_ => value.as_f64().ok_or_else(mismatch)?,
+		}),
+		"Int" => MetadataValue::Int(value.as_i64().ok_or_else(mismatch)?),
+		"UInt" => MetadataValue::UInt(value.as_u64().ok_or_else(mismatch)?),
+		"Bool" => MetadataValue::Bool(value.as_bool().ok_or_else(mismatch)?),
+		"Array" => MetadataValue::Array(
+			value
+				.as_array()
+				.ok_or_else(mismatch)?
+				.iter()
+				.map(decode)
+				.collect::<Result<_>>()?,
+		),
+		"Dict" => MetadataValue::Dict(
+			value
+				.as_object()
+				.ok_or_else(mismatch)?
+				.iter()
+				.map(|(key, value)| decode(value).map(|value| (key.clone(), value)))
+				.collect::<Result<_>>()?,
+		),
+		"Unsupported" => MetadataValue::__Unsupported,
+		_ => return Err(invalid(format!("unknown value type {}", ty))),
+	})
+}
+
+pub(crate) fn invalid<T: ToString>(msg: T) -> Error {
+	Error::Deserialize(msg.to_string())
+}
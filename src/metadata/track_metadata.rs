@@ -0,0 +1,206 @@
+// SPDX-License-Identifier: MPL-2.0
+use super::{Metadata, MetadataValue};
+use crate::error::{Error, Result};
+use time::{Duration, OffsetDateTime};
+use zbus::zvariant::OwnedObjectPath;
+
+/// A strongly typed view of every field defined in the MPRIS metadata guidelines.
+///
+/// Fields the player did not provide are `None`; keys outside the guidelines are dropped.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TrackMetadata {
+	/// `mpris:trackid`: A unique identity for this track within the context of an MPRIS object.
+	pub track_id: Option<OwnedObjectPath>,
+	/// `mpris:length`: The length of the track.
+	pub length: Option<Duration>,
+	/// `mpris:artUrl`: The location of an image representing the track or album.
+	pub art_url: Option<String>,
+	/// `xesam:album`: The album name.
+	pub album: Option<String>,
+	/// `xesam:albumArtist`: The album artist(s).
+	pub album_artists: Option<Vec<String>>,
+	/// `xesam:artist`: The track artist(s).
+	pub artists: Option<Vec<String>>,
+	/// `xesam:asText`: The track lyrics.
+	pub lyrics: Option<String>,
+	/// `xesam:audioBPM`: The speed of the music, in beats per minute.
+	pub audio_bpm: Option<u64>,
+	/// `xesam:autoRating`: An automatically-generated rating, in the range 0.0 to 1.0.
+	pub auto_rating: Option<f64>,
+	/// `xesam:comment`: A (list of) freeform comment(s).
+	pub comment: Option<Vec<String>>,
+	/// `xesam:composer`: The composer(s) of the track.
+	pub composer: Option<Vec<String>>,
+	/// `xesam:contentCreated`: When the track was created.
+	pub content_created: Option<OffsetDateTime>,
+	/// `xesam:discNumber`: The disc number on the album that this track is from.
+	pub disc_number: Option<u64>,
+	/// `xesam:firstUsed`: When the track was first played.
+	pub first_used: Option<OffsetDateTime>,
+	/// `xesam:genre`: The genre(s) of the track.
+	pub genre: Option<Vec<String>>,
+	/// `xesam:lastUsed`: When the track was last played.
+	pub last_used: Option<OffsetDateTime>,
+	/// `xesam:lyricist`: The lyricist(s) of the track.
+	pub lyricist: Option<Vec<String>>,
+	/// `xesam:title`: The track title.
+	pub title: Option<String>,
+	/// `xesam:trackNumber`: The track number on the album that this track is from.
+	pub track_number: Option<u64>,
+	/// `xesam:url`: The location of the media file.
+	pub url: Option<String>,
+	/// `xesam:useCount`: The number of times the track has been played.
+	pub use_count: Option<u64>,
+	/// `xesam:userRating`: The user's rating of the track, in the range 0.0 to 1.0.
+	pub user_rating: Option<f64>,
+}
+
+impl TryFrom<Metadata> for TrackMetadata {
+	type Error = Error;
+
+	/// Converts the metadata, failing if any known field holds a value of the wrong type.
+	fn try_from(metadata: Metadata) -> Result<Self> {
+		let m = &metadata;
+		Ok(Self {
+			track_id: field(m, "mpris:trackid", "Str (ObjectPath)", m.track_id())?,
+			length: field(m, "mpris:length", "Int", m.length())?,
+			art_url: field(m, "mpris:artUrl", "Str", m.art_url())?,
+			album: field(m, "xesam:album", "Str", m.album())?,
+			album_artists: field(m, "xesam:albumArtist", "Array", m.album_artists())?,
+			artists: field(m, "xesam:artist", "Array", m.artists())?,
+			lyrics: field(m, "xesam:asText", "Str", m.lyrics())?,
+			audio_bpm: field(m, "xesam:audioBPM", "UInt", m.audio_bpm())?,
+			auto_rating: field(m, "xesam:autoRating", "Double", m.auto_rating())?,
+			comment: field(m, "xesam:comment", "Array", m.comment())?,
+			composer: field(m, "xesam:composer", "Array", m.composer())?,
+			content_created: field(
+				m,
+				"xesam:contentCreated",
+				"String (DateTime)",
+				m.content_created(),
+			)?,
+			disc_number: field(m, "xesam:discNumber", "UInt", m.disc_number())?,
+			first_used: field(m, "xesam:firstUsed", "String (DateTime)", m.first_used())?,
+			genre: field(m, "xesam:genre", "Array", m.genre())?,
+			last_used: field(m, "xesam:lastUsed", "String (DateTime)", m.last_used())?,
+			lyricist: field(m, "xesam:lyricist", "Array", m.lyricist())?,
+			title: field(m, "xesam:title", "Str", m.title())?,
+			track_number: field(m, "xesam:trackNumber", "UInt", m.track_number())?,
+			url: field(m, "xesam:url", "Str", m.url())?,
+			use_count: field(m, "xesam:useCount", "UInt", m.use_count())?,
+			user_rating: field(m, "xesam:userRating", "Double", m.user_rating())?,
+		})
+	}
+}
+
+impl From<TrackMetadata> for Metadata {
+	fn from(track: TrackMetadata) -> Self {
+		let mut builder = Metadata::builder();
+		if let Some(track_id) = track.track_id {
+			builder = builder.track_id(track_id);
+		}
+		if let Some(length) = track.length {
+			builder = builder.length(length);
+		}
+		if let Some(art_url) = track.art_url {
+			builder = builder.art_url(art_url);
+		}
+		if let Some(album) = track.album {
+			builder = builder.album(album);
+		}
+		if let Some(album_artists) = track.album_artists {
+			builder = builder.album_artists(album_artists);
+		}
+		if let Some(artists) = track.artists {
+			builder = builder.artists(artists);
+		}
+		if let Some(lyrics) = track.lyrics {
+			builder = builder.lyrics(lyrics);
+		}
+		if let Some(audio_bpm) = track.audio_bpm {
+			builder = builder.insert("xesam:audioBPM", integer(audio_bpm));
+		}
+		if let Some(auto_rating) = track.auto_rating {
+			builder = builder.auto_rating(auto_rating);
+		}
+		if let Some(comment) = track.comment {
+			builder = builder.comment(comment);
+		}
+		if let Some(composer) = track.composer {
+			builder = builder.composer(composer);
+		}
+		if let Some(content_created) = track.content_created {
+			builder = builder.content_created(content_created);
+		}
+		if let Some(disc_number) = track.disc_number {
+			builder = builder.insert("xesam:discNumber", integer(disc_number));
+		}
+		if let Some(first_used) = track.first_used {
+			builder = builder.first_used(first_used);
+		}
+		if let Some(genre) = track.genre {
+			builder = builder.genre(genre);
+		}
+		if let Some(last_used) = track.last_used {
+			builder = builder.last_used(last_used);
+		}
+		if let Some(lyricist) = track.lyricist {
+			builder = builder.lyricist(lyricist);
+		}
+		if let Some(title) = track.title {
+			builder = builder.title(title);
+		}
+		if let Some(track_number) = track.track_number {
+			builder = builder.insert("xesam:trackNumber", integer(track_number));
+		}
+		if let Some(url) = track.url {
+			builder = builder.url(url);
+		}
+		if let Some(use_count) = track.use_count {
+			builder = builder.insert("xesam:useCount", integer(use_count));
+		}
+		if let Some(user_rating) = track.user_rating {
+			builder = builder.user_rating(user_rating);
+		}
+		builder.build()
+	}
+}
+
+/// Stores integer fields as `Int` like the builder and values read off the bus,
+/// unless they don't fit.
+fn integer(value: u64) -> MetadataValue {
+	i64::try_from(value).map_or(MetadataValue::UInt(value), MetadataValue::Int)
+}
+
+/// Turns a failed accessor into an error if the key was present, but couldn't be converted.
+fn field<T>(
+	metadata: &Metadata,
+	key: &str,
+	wanted: &'static str,
+	value: Option<T>,
+) -> Result<Option<T>> {
+	match value {
+		Some(value) => Ok(Some(value)),
+		None if metadata.inner.contains_key(key) => Err(metadata.field_error(key, wanted)),
+		None => Ok(None),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn round_trips_like_the_builder() {
+		let metadata = Metadata::builder()
+			.title("Title")
+			.audio_bpm(120)
+			.disc_number(1)
+			.track_number(3)
+			.use_count(7)
+			.build();
+		let track = TrackMetadata::try_from(metadata.clone()).unwrap();
+		assert_eq!(track.track_number, Some(3));
+		assert_eq!(Metadata::from(track), metadata);
+	}
+}
@@ -0,0 +1,181 @@
+// SPDX-License-Identifier: MPL-2.0
+use super::{parse_date, Metadata, MetadataValue};
+use std::fmt::{self, Display};
+use zbus::zvariant::ObjectPath;
+
+/// A way in which [Metadata] deviates from the MPRIS metadata guidelines, as found by [Metadata::validate].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+	/// `mpris:trackid` is missing, although it is required.
+	MissingTrackId,
+	/// `mpris:trackid` is not a valid D-Bus object path.
+	InvalidTrackId(String),
+	/// `mpris:trackid` was sent as a string (`s`) instead of an object path (`o`).
+	StringTrackId(String),
+	/// A known key holds a value of the wrong type.
+	IncorrectType {
+		key: String,
+		expected: &'static str,
+		actual: &'static str,
+	},
+	/// `mpris:length` is negative.
+	NegativeLength(i64),
+	/// A date field could not be parsed as an ISO 8601 date/time.
+	InvalidDate { key: String, value: String },
+	/// A URL field is not a well-formed URI.
+	InvalidUrl { key: String, value: String },
+}
+
+impl Display for Violation {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::MissingTrackId => write!(f, "mpris:trackid is missing"),
+			Self::InvalidTrackId(id) => write!(f, "mpris:trackid {:?} is not an object path", id),
+			Self::StringTrackId(id) => {
+				write!(
+					f,
+					"mpris:trackid {:?} is sent as a string, not an object path",
+					id
+				)
+			}
+			Self::IncorrectType {
+				key,
+				expected,
+				actual,
+			} => write!(f, "{} should be {}, but is {}", key, expected, actual),
+			Self::NegativeLength(length) => write!(f, "mpris:length is negative ({})", length),
+			Self::InvalidDate { key, value } => {
+				write!(f, "{} {:?} is not an ISO 8601 date", key, value)
+			}
+			Self::InvalidUrl { key, value } => write!(f, "{} {:?} is not a valid URI", key, value),
+		}
+	}
+}
+
+#[derive(Clone, Copy)]
+enum Kind {
+	TrackId,
+	Length,
+	Str,
+	Strings,
+	Integer,
+	Double,
+	Date,
+	Url,
+}
+
+const FIELDS: &[(&str, Kind)] = &[
+	("mpris:trackid", Kind::TrackId),
+	("mpris:length", Kind::Length),
+	("mpris:artUrl", Kind::Url),
+	("xesam:album", Kind::Str),
+	("xesam:albumArtist", Kind::Strings),
+	("xesam:artist", Kind::Strings),
+	("xesam:asText", Kind::Str),
+	("xesam:audioBPM", Kind::Integer),
+	("xesam:autoRating", Kind::Double),
+	("xesam:comment", Kind::Strings),
+	("xesam:composer", Kind::Strings),
+	("xesam:contentCreated", Kind::Date),
+	("xesam:discNumber", Kind::Integer),
+	("xesam:firstUsed", Kind::Date),
+	("xesam:genre", Kind::Strings),
+	("xesam:lastUsed", Kind::Date),
+	("xesam:lyricist", Kind::Strings),
+	("xesam:title", Kind::Str),
+	("xesam:trackNumber", Kind::Integer),
+	("xesam:url", Kind::Url),
+	("xesam:useCount", Kind::Integer),
+	("xesam:userRating", Kind::Double),
+];
+
+pub(super) fn validate(metadata: &Metadata) -> Vec<Violation> {
+	let mut violations = Vec::new();
+	if !metadata.inner.contains_key("mpris:trackid") {
+		violations.push(Violation::MissingTrackId);
+	}
+	for (key, kind) in FIELDS {
+		if let Some(value) = metadata.inner.get(*key) {
+			check(metadata, key, *kind, value, &mut violations);
+		}
+	}
+	violations
+}
+
+/// Checks a field, using the signature it was received with where [MetadataValue] loses it.
+fn check(
+	metadata: &Metadata,
+	key: &str,
+	kind: Kind,
+	value: &MetadataValue,
+	violations: &mut Vec<Violation>,
+) {
+	let expected = match (kind, value) {
+		(Kind::TrackId, MetadataValue::Str(id)) => {
+			if ObjectPath::try_from(id.as_str()).is_err() {
+				violations.push(Violation::InvalidTrackId(id.clone()));
+			} else if metadata.received_signature(key) == Some("s") {
+				violations.push(Violation::StringTrackId(id.clone()));
+			}
+			return;
+		}
+		(Kind::Length, MetadataValue::Int(length)) => {
+			if *length < 0 {
+				violations.push(Violation::NegativeLength(*length));
+			}
+			return;
+		}
+		(Kind::Date, MetadataValue::Str(date)) => {
+			if parse_date(date).is_none() {
+				violations.push(Violation::InvalidDate {
+					key: key.to_string(),
+					value: date.clone(),
+				});
+			}
+			return;
+		}
+		(Kind::Url, MetadataValue::Str(url)) => {
+			if !is_uri(url) {
+				violations.push(Violation::InvalidUrl {
+					key: key.to_string(),
+					value: url.clone(),
+				});
+			}
+			return;
+		}
+		(Kind::Strings, MetadataValue::Array(values))
+			if values.iter().all(|v| matches!(v, MetadataValue::Str(_))) =>
+		{
+			return
+		}
+		(Kind::Str, MetadataValue::Str(_))
+		| (Kind::Length, MetadataValue::UInt(_))
+		| (Kind::Integer, MetadataValue::Int(_) | MetadataValue::UInt(_))
+		| (Kind::Double, MetadataValue::Double(_)) => return,
+		(Kind::TrackId, _) => "Str (ObjectPath)",
+		(Kind::Length, _) | (Kind::Integer, _) => "Int",
+		(Kind::Str, _) => "Str",
+		(Kind::Strings, _) => "Array of Str",
+		(Kind::Double, _) => "Double",
+		(Kind::Date, _) => "Str (DateTime)",
+		(Kind::Url, _) => "Str (URI)",
+	};
+	violations.push(Violation::IncorrectType {
+		key: key.to_string(),
+		expected,
+		actual: value.variant(),
+	});
+}
+
+/// Checks for an RFC 3986 scheme followed by a non-empty remainder without whitespace.
+fn is_uri(s: &str) -> bool {
+	let (scheme, rest) = match s.split_once(':') {
+		Some(parts) => parts,
+		None => return false,
+	};
+	let mut chars = scheme.chars();
+	chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+		&& chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+		&& !rest.is_empty()
+		&& !rest.chars().any(char::is_whitespace)
+}
@@ -0,0 +1,285 @@
+// SPDX-License-Identifier: MPL-2.0
+pub mod bus;
+pub mod faults;
+
+use crate::{
+	error::Result,
+	metadata::Metadata,
+	player::{LoopStatus, PlaybackStatus},
+	server::{command::Command, MprisServer},
+};
+use async_channel::Receiver;
+use async_io::Timer;
+use std::{
+	ops::Deref,
+	sync::{Arc, Mutex, MutexGuard},
+};
+use time::Duration;
+use zbus::{names::OwnedWellKnownName, Connection};
+
+#[derive(Debug, Default)]
+struct Calls {
+	recorded: Vec<Command>,
+	/// The requests not yet acted upon by [MockPlayer::apply_calls].
+	pending: Vec<Command>,
+}
+
+#[derive(Debug, Default)]
+struct Playlist {
+	tracks: Vec<Metadata>,
+	current: usize,
+}
+
+/// A fake player for integration tests of MPRIS clients, such as applets,
+/// which don't want to launch a real player.
+///
+/// Its properties are scripted through the [MprisServer] setters it dereferences to,
+/// and the requests clients make are recorded instead of acted upon.
+/// Every capability is enabled, so no request is dropped.
+///
+/// It can also simulate playback of the tracks given to [MockPlayer::load]:
+/// [MockPlayer::apply_calls] acts on the requests like a real player would,
+/// and [MockPlayer::advance] moves playback forwards in virtual time,
+/// so position trackers can be tested deterministically.
+///
+/// Clones share the recorded calls and the tracks.
+#[derive(Debug, Clone)]
+pub struct MockPlayer {
+	server: MprisServer,
+	name: OwnedWellKnownName,
+	commands: Receiver<Command>,
+	calls: Arc<Mutex<Calls>>,
+	playlist: Arc<Mutex<Playlist>>,
+}
+
+impl MockPlayer {
+	/// Exports a mock player on the given connection, e.g. to a private bus,
+	/// as `org.mpris.MediaPlayer2.<app_name>`, or with an instance suffix if that is taken.
+	pub async fn new(connection: &Connection, app_name: &str) -> Result<Self> {
+		let (server, commands) = MprisServer::with_commands(connection, app_name).await?;
+		server.set_can_quit(true).await?;
+		server.set_can_raise(true).await?;
+		server.set_can_go_next(true).await?;
+		server.set_can_go_previous(true).await?;
+		server.set_can_play(true).await?;
+		server.set_can_pause(true).await?;
+		server.set_can_seek(true).await?;
+		let name = server.register(app_name, false).await?;
+		Ok(Self {
+			server,
+			name,
+			commands,
+			calls: Arc::default(),
+			playlist: Arc::default(),
+		})
+	}
+
+	/// Exports a mock player on a new connection to the session bus.
+	pub async fn session(app_name: &str) -> Result<Self> {
+		Self::new(&Connection::session().await?, app_name).await
+	}
+
+	/// The bus name clients can find the player at.
+	pub fn name(&self) -> &OwnedWellKnownName {
+		&self.name
+	}
+
+	/// The requests made so far, oldest first.
+	pub fn calls(&self) -> Vec<Command> {
+		self.record().recorded.clone()
+	}
+
+	/// Takes the requests made so far, oldest first, so later checks only see newer ones.
+	pub fn take_calls(&self) -> Vec<Command> {
+		std::mem::take(&mut self.record().recorded)
+	}
+
+	/// Waits until at least `count` requests have been recorded, then returns them all,
+	/// oldest first.
+	///
+	/// Returns fewer if the player is gone before enough requests arrive.
+	pub async fn wait_for_calls(&self, count: usize) -> Vec<Command> {
+		while self.record().recorded.len() < count {
+			match self.commands.recv().await {
+				Ok(command) => self.record().push(command),
+				Err(_) => break,
+			}
+		}
+		self.calls()
+	}
+
+	/// Replaces the tracks to simulate playback of, stopped at the start of the first one.
+	///
+	/// Tracks need an `mpris:length` to ever end.
+	pub async fn load(&self, tracks: Vec<Metadata>) -> Result<()> {
+		let first = tracks.first().cloned().unwrap_or_default();
+		*self.playlist() = Playlist { tracks, current: 0 };
+		self.server
+			.set_playback_status(PlaybackStatus::Stopped)
+			.await?;
+		self.server.set_metadata(first).await?;
+		self.server.set_position(Duration::ZERO).await
+	}
+
+	/// Acts on the requests made since the last call, like a real player would:
+	/// changing the playback status, switching tracks, and seeking with `Seeked`.
+	///
+	/// Returns the requests acted upon.
+	pub async fn apply_calls(&self) -> Result<Vec<Command>> {
+		let pending = std::mem::take(&mut self.record().pending);
+		for command in &pending {
+			self.apply(command).await?;
+		}
+		Ok(pending)
+	}
+
+	/// Moves playback forwards by `elapsed` of virtual time, at the playback rate while playing,
+	/// moving on to the next track whenever the current one ends, as the loop status says.
+	pub async fn advance(&self, elapsed: Duration) -> Result<()> {
+		if self.server.playback_status().await? != PlaybackStatus::Playing {
+			return Ok(());
+		}
+		let mut position = self.server.position().await? + elapsed * self.server.rate().await?;
+		while let Some(length) = self.server.metadata().await?.length() {
+			if position < length || length <= Duration::ZERO {
+				break;
+			}
+			position -= length;
+			if !self.skip(1, true).await? {
+				return Ok(());
+			}
+		}
+		self.server.set_position(position).await
+	}
+
+	/// Simulates playback in real time until the connection fails,
+	/// acting on requests and advancing every `tick`.
+	pub async fn run(&self, tick: std::time::Duration) -> Result<()> {
+		let elapsed = Duration::try_from(tick).unwrap_or(Duration::MAX);
+		loop {
+			Timer::after(tick).await;
+			self.apply_calls().await?;
+			self.advance(elapsed).await?;
+		}
+	}
+
+	async fn apply(&self, command: &Command) -> Result<()> {
+		let status = self.server.playback_status().await?;
+		match command {
+			Command::Play => {
+				self.server
+					.set_playback_status(PlaybackStatus::Playing)
+					.await
+			}
+			Command::Pause if status == PlaybackStatus::Playing => {
+				self.server
+					.set_playback_status(PlaybackStatus::Paused)
+					.await
+			}
+			Command::PlayPause => {
+				let status = if status == PlaybackStatus::Playing {
+					PlaybackStatus::Paused
+				} else {
+					PlaybackStatus::Playing
+				};
+				self.server.set_playback_status(status).await
+			}
+			Command::Stop => {
+				self.server
+					.set_playback_status(PlaybackStatus::Stopped)
+					.await?;
+				self.server.set_position(Duration::ZERO).await
+			}
+			Command::Next => self.skip(1, false).await.map(|_| ()),
+			Command::Previous => self.skip(-1, false).await.map(|_| ()),
+			Command::Seek(offset) => {
+				let position = self.server.position().await? + *offset;
+				let length = self.server.metadata().await?.length();
+				if length.is_some_and(|length| position >= length) {
+					self.skip(1, false).await.map(|_| ())
+				} else {
+					self.server.seeked(position.max(Duration::ZERO)).await
+				}
+			}
+			Command::SetPosition(_, position) => self.server.seeked(*position).await,
+			_ => Ok(()),
+		}
+	}
+
+	/// Moves `offset` tracks forwards or backwards, from the start of the new track.
+	///
+	/// At the end of the tracks, playback wraps around if the loop status is `Playlist`,
+	/// and otherwise stops, returning false. If `ended` is true, the current track finished
+	/// playing, and is repeated if the loop status is `Track`.
+	async fn skip(&self, offset: isize, ended: bool) -> Result<bool> {
+		let loop_status = self.server.loop_status().await?;
+		let next = {
+			let mut playlist = self.playlist();
+			let count = playlist.tracks.len() as isize;
+			let target = if ended && loop_status == LoopStatus::Track {
+				Some(playlist.current as isize)
+			} else {
+				let target = playlist.current as isize + offset;
+				match loop_status {
+					LoopStatus::Playlist if count > 0 => Some(target.rem_euclid(count)),
+					_ => Some(target).filter(|target| (0..count).contains(target)),
+				}
+			};
+			target.map(|target| {
+				playlist.current = target as usize;
+				playlist.tracks[playlist.current].clone()
+			})
+		};
+		match next {
+			Some(metadata) => {
+				self.server.set_metadata(metadata).await?;
+				self.server.set_position(Duration::ZERO).await?;
+				Ok(true)
+			}
+			None if offset < 0 => {
+				self.server.seeked(Duration::ZERO).await?;
+				Ok(true)
+			}
+			None => {
+				self.server
+					.set_playback_status(PlaybackStatus::Stopped)
+					.await?;
+				self.server.set_position(Duration::ZERO).await?;
+				Ok(false)
+			}
+		}
+	}
+
+	/// Moves the requests which arrived into the recording.
+	fn record(&self) -> MutexGuard<'_, Calls> {
+		let mut calls = self
+			.calls
+			.lock()
+			.unwrap_or_else(|poisoned| poisoned.into_inner());
+		while let Ok(command) = self.commands.try_recv() {
+			calls.push(command);
+		}
+		calls
+	}
+
+	fn playlist(&self) -> MutexGuard<'_, Playlist> {
+		self.playlist
+			.lock()
+			.unwrap_or_else(|poisoned| poisoned.into_inner())
+	}
+}
+
+impl Calls {
+	fn push(&mut self, command: Command) {
+		self.recorded.push(command.clone());
+		self.pending.push(command);
+	}
+}
+
+impl Deref for MockPlayer {
+	type Target = MprisServer;
+
+	fn deref(&self) -> &Self::Target {
+		&self.server
+	}
+}
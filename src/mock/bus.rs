@@ -0,0 +1,99 @@
+// SPDX-License-Identifier: MPL-2.0
+use super::MockPlayer;
+use crate::error::{Error, Result};
+use std::{
+	io::{self, BufRead, BufReader},
+	process::{Child, Command, Stdio},
+};
+use zbus::{Connection, ConnectionBuilder};
+
+/// A private `dbus-daemon`, so tests can export and discover players
+/// without touching the real session bus, or each other.
+///
+/// A bus daemon is used rather than a peer-to-peer connection, as discovery relies on
+/// the daemon's `ListNames` and `NameOwnerChanged`.
+/// The daemon is killed when this is dropped.
+#[derive(Debug)]
+pub struct TestBus {
+	daemon: Child,
+	address: String,
+	connection: Connection,
+}
+
+impl TestBus {
+	/// Launches a `dbus-daemon` with the session bus configuration, and connects to it.
+	///
+	/// Fails with an I/O error if `dbus-daemon` isn't installed.
+	pub async fn new() -> Result<Self> {
+		let (mut daemon, address) = blocking::unblock(launch_daemon).await?;
+		match connect(&address).await {
+			Ok(connection) => Ok(Self {
+				daemon,
+				address,
+				connection,
+			}),
+			Err(err) => {
+				let _ = daemon.kill();
+				let _ = daemon.wait();
+				Err(err)
+			}
+		}
+	}
+
+	/// The address of the bus, e.g. for `DBUS_SESSION_BUS_ADDRESS` in a player under test.
+	pub fn address(&self) -> &str {
+		&self.address
+	}
+
+	/// A connection to the bus, shared by the clients under test.
+	pub fn connection(&self) -> &Connection {
+		&self.connection
+	}
+
+	/// Opens a new connection to the bus, with its own unique name.
+	pub async fn connect(&self) -> Result<Connection> {
+		connect(&self.address).await
+	}
+
+	/// Exports a [MockPlayer] on the bus, with its own connection,
+	/// as `org.mpris.MediaPlayer2.<app_name>`, or with an instance suffix if that is taken.
+	pub async fn mock_player(&self, app_name: &str) -> Result<MockPlayer> {
+		MockPlayer::new(&self.connect().await?, app_name).await
+	}
+}
+
+impl Drop for TestBus {
+	fn drop(&mut self) {
+		let _ = self.daemon.kill();
+		let _ = self.daemon.wait();
+	}
+}
+
+async fn connect(address: &str) -> Result<Connection> {
+	Ok(ConnectionBuilder::address(address)?.build().await?)
+}
+
+/// Starts the daemon, and reads the address it prints once it is listening.
+fn launch_daemon() -> Result<(Child, String)> {
+	let mut daemon = Command::new("dbus-daemon")
+		.args(["--session", "--nofork", "--nopidfile", "--print-address"])
+		.stdin(Stdio::null())
+		.stdout(Stdio::piped())
+		.spawn()?;
+	let mut address = String::new();
+	let read = daemon
+		.stdout
+		.take()
+		.map(|stdout| BufReader::new(stdout).read_line(&mut address));
+	match read {
+		Some(Ok(len)) if len > 0 => Ok((daemon, address.trim_end().to_string())),
+		_ => {
+			let _ = daemon.kill();
+			let _ = daemon.wait();
+			Err(Error::Io(io::Error::new(
+				io::ErrorKind::UnexpectedEof,
+				"dbus-daemon exited without printing its address",
+			)))
+		}
+	}
+}
@@ -0,0 +1,375 @@
+// SPDX-License-Identifier: MPL-2.0
+use crate::{error::Result, media_player::MediaPlayer, server::MPRIS_PATH};
+use async_io::Timer;
+use futures_util::StreamExt;
+use std::{
+	collections::{HashMap, HashSet},
+	fmt::Write,
+	marker::PhantomData,
+	sync::{Arc, Mutex, MutexGuard},
+	time::Duration,
+};
+use zbus::{
+	fdo::{self, PropertiesProxy, RequestNameFlags},
+	names::{BusName, InterfaceName, MemberName, OwnedWellKnownName, WellKnownName},
+	zvariant::{OwnedValue, Structure, Value},
+	CacheProperties, Connection, DispatchResult, Interface, MatchRule, Message, MessageStream,
+	MessageType, ObjectServer, Proxy, SignalContext, Task,
+};
+
+#[derive(Debug, Default)]
+struct Faults {
+	delay: Duration,
+	unsupported_properties: HashSet<String>,
+	unsupported_methods: HashSet<String>,
+	malformed: HashMap<String, OwnedValue>,
+}
+
+/// A hostile version of another player, to check that clients degrade gracefully.
+///
+/// It relays the `org.mpris.MediaPlayer2` and `org.mpris.MediaPlayer2.Player` interfaces
+/// of the wrapped player under a name of its own, and can be told at any time to
+/// delay its replies, fail properties and methods with `NotSupported`, send malformed values,
+/// or vanish from the bus. Other interfaces aren't relayed.
+#[derive(Debug)]
+pub struct FaultyPlayer {
+	connection: Connection,
+	name: OwnedWellKnownName,
+	faults: Arc<Mutex<Faults>>,
+	/// Relays the wrapped player's signals, until the player disconnects.
+	signals: Option<Task<()>>,
+}
+
+impl FaultyPlayer {
+	/// Relays `player` on the given connection, as `org.mpris.MediaPlayer2.<app_name>`.
+	///
+	/// Use a connection of its own, so signals of other objects aren't mixed up with the relayed ones.
+	pub async fn new(
+		player: &MediaPlayer,
+		connection: &Connection,
+		app_name: &str,
+	) -> Result<Self> {
+		let faults = Arc::<Mutex<Faults>>::default();
+		let target = player.destination().to_owned();
+		let root = relay::<Root>(player, &faults).await?;
+		let player_interface = relay::<PlayerKind>(player, &faults).await?;
+		let object_server = connection.object_server();
+		object_server.at(MPRIS_PATH, root).await?;
+		object_server.at(MPRIS_PATH, player_interface).await?;
+
+		let rule = MatchRule::builder()
+			.msg_type(MessageType::Signal)
+			.sender(target)?
+			.path(MPRIS_PATH)?
+			.build();
+		let signals = MessageStream::for_match_rule(rule, player.connection(), None).await?;
+		let task = connection.executor().spawn(
+			forward_signals(signals, connection.clone(), faults.clone()),
+			"relay MPRIS signals",
+		);
+
+		let name = WellKnownName::try_from(format!("org.mpris.MediaPlayer2.{app_name}"))
+			.map_err(zbus::Error::from)?;
+		connection
+			.request_name_with_flags(&name, RequestNameFlags::DoNotQueue.into())
+			.await?;
+		Ok(Self {
+			connection: connection.clone(),
+			name: name.into(),
+			faults,
+			signals: Some(task),
+		})
+	}
+
+	/// The bus name clients can find the player at.
+	pub fn name(&self) -> &OwnedWellKnownName {
+		&self.name
+	}
+
+	/// Delays every reply and signal by `delay`.
+	pub fn set_delay(&self, delay: Duration) {
+		self.faults().delay = delay;
+	}
+
+	/// Fails reading `property` with `NotSupported`, and leaves it out of `GetAll`
+	/// and `PropertiesChanged`.
+	pub fn fail_property(&self, property: &str) {
+		self.faults()
+			.unsupported_properties
+			.insert(property.to_string());
+	}
+
+	/// Fails calls of `method` with `NotSupported`, instead of relaying them.
+	pub fn fail_method(&self, method: &str) {
+		self.faults().unsupported_methods.insert(method.to_string());
+	}
+
+	/// Replaces the value of `property` with `value`, e.g. one of the wrong type,
+	/// wherever it is sent.
+	pub fn malform_property(&self, property: &str, value: OwnedValue) {
+		self.faults().malformed.insert(property.to_string(), value);
+	}
+
+	/// Removes all faults, relaying the player faithfully again.
+	pub fn heal(&self) {
+		*self.faults() = Faults::default();
+	}
+
+	/// Vanishes from the bus, as if the player crashed: signals stop being relayed,
+	/// the name is released and the object removed, so further calls fail.
+	pub async fn disconnect(&mut self) -> Result<()> {
+		self.signals = None;
+		self.connection.release_name(&self.name).await?;
+		let object_server = self.connection.object_server();
+		object_server
+			.remove::<Relay<PlayerKind>, _>(MPRIS_PATH)
+			.await?;
+		object_server.remove::<Relay<Root>, _>(MPRIS_PATH).await?;
+		Ok(())
+	}
+
+	fn faults(&self) -> MutexGuard<'_, Faults> {
+		lock(&self.faults)
+	}
+}
+
+trait RelayedInterface: Send + Sync + 'static {
+	const NAME: &'static str;
+}
+
+struct Root;
+
+impl RelayedInterface for Root {
+	const NAME: &'static str = "org.mpris.MediaPlayer2";
+}
+
+struct PlayerKind;
+
+impl RelayedInterface for PlayerKind {
+	const NAME: &'static str = "org.mpris.MediaPlayer2.Player";
+}
+
+/// One interface of the wrapped player, relayed through uncached proxies.
+struct Relay<K> {
+	proxy: Proxy<'static>,
+	properties: PropertiesProxy<'static>,
+	faults: Arc<Mutex<Faults>>,
+	kind: PhantomData<K>,
+}
+
+async fn relay<K: RelayedInterface>(
+	player: &MediaPlayer,
+	faults: &Arc<Mutex<Faults>>,
+) -> Result<Relay<K>> {
+	let proxy = zbus::ProxyBuilder::new_bare(player.connection())
+		.destination(player.destination().to_owned())?
+		.path(MPRIS_PATH)?
+		.interface(K::NAME)?
+		.cache_properties(CacheProperties::No)
+		.build()
+		.await?;
+	let properties = PropertiesProxy::builder(player.connection())
+		.destination(player.destination().to_owned())?
+		.path(MPRIS_PATH)?
+		.build()
+		.await?;
+	Ok(Relay {
+		proxy,
+		properties,
+		faults: faults.clone(),
+		kind: PhantomData,
+	})
+}
+
+impl<K> Relay<K> {
+	/// Waits for the configured delay.
+	async fn delay(&self) {
+		let delay = lock(&self.faults).delay;
+		if !delay.is_zero() {
+			Timer::after(delay).await;
+		}
+	}
+}
+
+#[zbus::export::async_trait::async_trait]
+impl<K: RelayedInterface> Interface for Relay<K> {
+	fn name() -> InterfaceName<'static> {
+		InterfaceName::from_static_str_unchecked(K::NAME)
+	}
+
+	async fn get(&self, property_name: &str) -> Option<fdo::Result<OwnedValue>> {
+		self.delay().await;
+		{
+			let faults = lock(&self.faults);
+			if faults.unsupported_properties.contains(property_name) {
+				return Some(Err(not_supported(property_name)));
+			}
+			if let Some(value) = faults.malformed.get(property_name) {
+				return Some(Ok(value.clone()));
+			}
+		}
+		Some(
+			self.proxy
+				.get_property::<OwnedValue>(property_name)
+				.await
+				.map_err(fdo::Error::from),
+		)
+	}
+
+	async fn get_all(&self) -> HashMap<String, OwnedValue> {
+		self.delay().await;
+		let mut values = self
+			.properties
+			.get_all(InterfaceName::from_static_str_unchecked(K::NAME))
+			.await
+			.unwrap_or_default();
+		apply(&lock(&self.faults), &mut values);
+		values
+	}
+
+	async fn set_mut(
+		&mut self,
+		property_name: &str,
+		value: &Value<'_>,
+		_ctxt: &SignalContext<'_>,
+	) -> Option<fdo::Result<()>> {
+		self.delay().await;
+		if lock(&self.faults)
+			.unsupported_properties
+			.contains(property_name)
+		{
+			return Some(Err(not_supported(property_name)));
+		}
+		let value = OwnedValue::from(value.clone());
+		Some(
+			self.proxy
+				.set_property(property_name, Value::from(value))
+				.await,
+		)
+	}
+
+	fn call<'call>(
+		&'call self,
+		_server: &'call ObjectServer,
+		connection: &'call Connection,
+		msg: &'call Message,
+		name: MemberName<'call>,
+	) -> DispatchResult<'call> {
+		DispatchResult::new_async(connection, msg, async move {
+			self.delay().await;
+			if lock(&self.faults)
+				.unsupported_methods
+				.contains(name.as_str())
+			{
+				return Err(not_supported(&name));
+			}
+			let reply = match msg.body::<Structure<'_>>() {
+				Ok(args) => self.proxy.call_method(name.as_str(), &args).await,
+				Err(_) => self.proxy.call_method(name.as_str(), &()).await,
+			};
+			reply.map(|_| ()).map_err(fdo::Error::from)
+		})
+	}
+
+	fn call_mut<'call>(
+		&'call mut self,
+		_server: &'call ObjectServer,
+		_connection: &'call Connection,
+		_msg: &'call Message,
+		_name: MemberName<'call>,
+	) -> DispatchResult<'call> {
+		DispatchResult::NotFound
+	}
+
+	/// Only names the interface, as its members are those of the wrapped player.
+	fn introspect_to_writer(&self, writer: &mut dyn Write, level: usize) {
+		let _ = writeln!(
+			writer,
+			r#"{:indent$}<interface name="{}"/>"#,
+			"",
+			K::NAME,
+			indent = level
+		);
+	}
+}
+
+/// Re-emits the wrapped player's signals as the relay's own.
+async fn forward_signals(
+	mut signals: MessageStream,
+	connection: Connection,
+	faults: Arc<Mutex<Faults>>,
+) {
+	while let Some(Ok(message)) = signals.next().await {
+		let Ok(header) = message.header() else {
+			continue;
+		};
+		let (Ok(Some(interface)), Ok(Some(member))) = (header.interface(), header.member()) else {
+			continue;
+		};
+		let delay = lock(&faults).delay;
+		if !delay.is_zero() {
+			Timer::after(delay).await;
+		}
+		let sent = if member.as_str() == "PropertiesChanged" {
+			let Ok((changed_interface, mut changed, mut invalidated)) =
+				message.body::<(String, HashMap<String, OwnedValue>, Vec<String>)>()
+			else {
+				continue;
+			};
+			{
+				let faults = lock(&faults);
+				for property in &faults.unsupported_properties {
+					if changed.remove(property).is_some() {
+						invalidated.push(property.clone());
+					}
+				}
+				apply(&faults, &mut changed);
+			}
+			connection
+				.emit_signal(
+					None::<BusName<'_>>,
+					MPRIS_PATH,
+					interface,
+					member,
+					&(changed_interface, changed, invalidated),
+				)
+				.await
+		} else {
+			match message.body::<Structure<'_>>() {
+				Ok(args) => {
+					connection
+						.emit_signal(None::<BusName<'_>>, MPRIS_PATH, interface, member, &args)
+						.await
+				}
+				Err(_) => {
+					connection
+						.emit_signal(None::<BusName<'_>>, MPRIS_PATH, interface, member, &())
+						.await
+				}
+			}
+		};
+		if sent.is_err() {
+			break;
+		}
+	}
+}
+
+/// Drops unsupported properties and substitutes malformed ones.
+fn apply(faults: &Faults, values: &mut HashMap<String, OwnedValue>) {
+	values.retain(|name, _| !faults.unsupported_properties.contains(name));
+	for (name, value) in values.iter_mut() {
+		if let Some(malformed) = faults.malformed.get(name) {
+			*value = malformed.clone();
+		}
+	}
+}
+
+fn not_supported(member: &str) -> fdo::Error {
+	fdo::Error::NotSupported(format!("{member} is not supported"))
+}
+
+fn lock(faults: &Mutex<Faults>) -> MutexGuard<'_, Faults> {
+	faults
+		.lock()
+		.unwrap_or_else(|poisoned| poisoned.into_inner())
+}
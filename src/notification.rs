@@ -0,0 +1,137 @@
+// SPDX-License-Identifier: MPL-2.0
+use crate::{
+	bindings::notifications::NotificationsProxy,
+	error::Result,
+	metadata::{path_to_file_url, Metadata},
+	player::Player,
+};
+use futures_util::StreamExt;
+use std::{collections::HashMap, path::PathBuf};
+use zbus::{zvariant::Value, Connection};
+
+/// A desktop notification describing a track.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notification {
+	/// The notification's title, usually the track title.
+	pub summary: String,
+	/// The notification's body, usually the artists and album.
+	///
+	/// Servers may render it as markup, so `&`, `<` and `>` must be escaped.
+	pub body: String,
+	/// The image to show alongside the notification, if any.
+	///
+	/// Notification servers only load local images, so this is only set for `file://` art.
+	pub image: Option<PathBuf>,
+}
+
+impl From<&Metadata> for Notification {
+	fn from(metadata: &Metadata) -> Self {
+		let summary = metadata
+			.title()
+			.or_else(|| metadata.url())
+			.unwrap_or_else(|| "Unknown track".to_string());
+		let artists = metadata
+			.artists()
+			.map(|artists| escape_markup(&artists.join(", ")))
+			.filter(|artists| !artists.is_empty());
+		let album = metadata.album().map(|album| escape_markup(&album));
+		let body = match (artists, album) {
+			(Some(artists), Some(album)) => format!("{} — {}", artists, album),
+			(Some(artists), None) => artists,
+			(None, Some(album)) => album,
+			(None, None) => String::new(),
+		};
+		Self {
+			summary,
+			body,
+			image: metadata.art_path(),
+		}
+	}
+}
+
+/// Posts desktop notifications for track changes through `org.freedesktop.Notifications`.
+///
+/// Each notification replaces the previous one, so only the current track is ever shown.
+#[derive(Debug, Clone)]
+pub struct TrackNotifier {
+	proxy: NotificationsProxy<'static>,
+	app_name: String,
+	last_id: u32,
+}
+
+impl TrackNotifier {
+	/// Creates a notifier that posts notifications under the given application name.
+	pub async fn new<S: Into<String>>(connection: &Connection, app_name: S) -> Result<Self> {
+		Ok(Self {
+			proxy: NotificationsProxy::new(connection).await?,
+			app_name: app_name.into(),
+			last_id: 0,
+		})
+	}
+
+	/// Shows a notification, replacing the previous one.
+	pub async fn show(&mut self, notification: &Notification) -> Result<()> {
+		let mut hints = HashMap::new();
+		if let Some(image) = &notification.image {
+			hints.insert("image-path", Value::from(path_to_file_url(image)));
+		}
+		self.last_id = self
+			.proxy
+			.notify(
+				&self.app_name,
+				self.last_id,
+				"",
+				&notification.summary,
+				&notification.body,
+				&[],
+				hints,
+				-1,
+			)
+			.await?;
+		Ok(())
+	}
+
+	/// Shows a notification for the given metadata, replacing the previous one.
+	pub async fn show_metadata(&mut self, metadata: &Metadata) -> Result<()> {
+		self.show(&Notification::from(metadata)).await
+	}
+
+	/// Posts a notification every time the player's track changes, until the player goes away.
+	///
+	/// Metadata updates that don't change the track, such as art arriving late, are ignored,
+	/// and so are updates which fail to be read.
+	pub async fn watch(&mut self, player: &Player) -> Result<()> {
+		let mut changes = player.receive_metadata_changed().await;
+		let mut current: Option<Metadata> = None;
+		while let Some(change) = changes.next().await {
+			let Ok(metadata) = change.get().await.map(Metadata::from) else {
+				continue;
+			};
+			if metadata.is_empty() {
+				continue;
+			}
+			let same_track = current
+				.as_ref()
+				.is_some_and(|current| current.same_track(&metadata));
+			if !same_track {
+				self.show_metadata(&metadata).await?;
+			}
+			current = Some(metadata);
+		}
+		Ok(())
+	}
+}
+
+/// Escapes the characters with a meaning in the notification body markup.
+fn escape_markup(text: &str) -> String {
+	let mut escaped = String::with_capacity(text.len());
+	for c in text.chars() {
+		match c {
+			'&' => escaped.push_str("&amp;"),
+			'<' => escaped.push_str("&lt;"),
+			'>' => escaped.push_str("&gt;"),
+			c => escaped.push(c),
+		}
+	}
+	escaped
+}
@@ -1,19 +1,23 @@
 // SPDX-License-Identifier: MPL-2.0
+pub mod snapshot;
+
 use crate::{
 	bindings::{media_player::MediaPlayer2Proxy, player::PlayerProxy},
+	check_mpris_name,
 	error::{Error, Result},
-	handle_optional,
+	get_all_properties, handle_optional,
 	media_player::MediaPlayer,
-	metadata::Metadata,
+	metadata::{Metadata, MetadataValue},
 	track::TrackId,
 };
 use std::{
+	collections::HashMap,
 	fmt::{self, Display},
 	ops::Deref,
 	str::FromStr,
 };
 use time::Duration;
-use zbus::{names::OwnedBusName, Connection};
+use zbus::{names::OwnedBusName, zvariant::OwnedValue, Connection, ConnectionBuilder};
 
 #[derive(Debug, Clone)]
 pub struct Player {
@@ -24,13 +28,26 @@ impl Player {
 	/// Creates a new instance of the `org.mpris.MediaPlayer2.Player` interface.
 	pub async fn new(connection: &Connection, name: OwnedBusName) -> Result<Self> {
 		PlayerProxy::builder(connection)
-			.destination(name)?
+			.destination(check_mpris_name(name)?)?
 			.build()
 			.await
 			.map(Self::from)
 			.map_err(Error::from)
 	}
 
+	/// Connects to the session bus and creates an instance of the player with the given name,
+	/// for tools which don't otherwise need a [Connection].
+	pub async fn connect_session(name: OwnedBusName) -> Result<Self> {
+		Self::new(&Connection::session().await?, name).await
+	}
+
+	/// Connects to the bus at the given D-Bus address and creates an instance of the player
+	/// with the given name.
+	pub async fn connect_address(address: &str, name: OwnedBusName) -> Result<Self> {
+		let connection = ConnectionBuilder::address(address)?.build().await?;
+		Self::new(&connection, name).await
+	}
+
 	/// Returns this player's `org.mpris.MediaPlayer2` instance
 	pub async fn media_player(&self) -> Result<MediaPlayer> {
 		let proxy = MediaPlayer2Proxy::builder(self.proxy.connection())
@@ -128,6 +145,33 @@ impl Player {
 			.map_err(Error::from)
 	}
 
+	/// Returns the metadata and the position within the current track, fetched together
+	/// so that they are guaranteed to be consistent with each other.
+	///
+	/// The position is None if the player doesn't support it.
+	pub async fn metadata_with_position(&self) -> Result<(Metadata, Option<Duration>)> {
+		let properties = get_all_properties(self.proxy.inner()).await?;
+		let metadata = properties
+			.get("Metadata")
+			.map(|value| HashMap::<String, OwnedValue>::try_from(value.clone()))
+			.transpose()
+			.map_err(zbus::Error::from)?
+			.map(Metadata::from)
+			.unwrap_or_default();
+		let position = properties
+			.get("Position")
+			.and_then(|value| MetadataValue::from(&**value).try_into_int().ok())
+			.map(Duration::microseconds);
+		Ok((metadata, position))
+	}
+
+	/// Returns the raw metadata map for the player, to be read through
+	/// [MetadataRef](crate::metadata::borrowed::MetadataRef)
+	/// without converting every value.
+	pub async fn raw_metadata(&self) -> Result<HashMap<String, OwnedValue>> {
+		self.proxy.metadata().await.map_err(Error::from)
+	}
+
 	/// Whether the current playlist is shuffled or not.
 	///
 	/// A value of false indicates that playback is progressing linearly through a playlist,
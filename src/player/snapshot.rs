@@ -0,0 +1,177 @@
+// SPDX-License-Identifier: MPL-2.0
+use super::Player;
+#[cfg(feature = "json")]
+use crate::metadata::json::{decode, encode, invalid};
+use crate::{error::Result, metadata::MetadataValue};
+use std::collections::{BTreeMap, BTreeSet};
+use zbus::{fdo::PropertiesProxy, names::InterfaceName};
+
+/// The interfaces captured by [PlayerStateSnapshot::capture], besides the player's own.
+const OPTIONAL_INTERFACES: [&str; 3] = [
+	"org.mpris.MediaPlayer2",
+	"org.mpris.MediaPlayer2.TrackList",
+	"org.mpris.MediaPlayer2.Playlists",
+];
+
+/// The properties of every interface of a player at one moment,
+/// to compare against a later snapshot with [PlayerStateSnapshot::diff].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlayerStateSnapshot {
+	/// The properties of each interface the player implements, by interface name.
+	pub properties: BTreeMap<String, BTreeMap<String, MetadataValue>>,
+}
+
+/// A property which differs between two snapshots.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PropertyChange {
+	pub interface: String,
+	pub property: String,
+	/// The value in the older snapshot, if it had the property.
+	pub old: Option<MetadataValue>,
+	/// The value in the newer snapshot, if it has the property.
+	pub new: Option<MetadataValue>,
+}
+
+/// The properties that differ between two snapshots, as returned by [PlayerStateSnapshot::diff].
+///
+/// The changes are sorted by interface, then property.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SnapshotDiff {
+	pub changes: Vec<PropertyChange>,
+}
+
+impl PlayerStateSnapshot {
+	/// Fetches the properties of the player's interfaces, one `GetAll` call each.
+	///
+	/// Properties the player fails to return, such as an unsupported `Fullscreen`, are left out.
+	pub async fn capture(player: &Player) -> Result<Self> {
+		let proxy = PropertiesProxy::builder(player.connection())
+			.destination(player.destination().to_owned())?
+			.path(player.path().to_owned())?
+			.build()
+			.await?;
+		let mut properties = BTreeMap::new();
+		let values = proxy.get_all(player.interface().to_owned()).await?;
+		properties.insert(player.interface().to_string(), values);
+		for interface in OPTIONAL_INTERFACES {
+			if let Ok(values) = proxy
+				.get_all(InterfaceName::from_static_str_unchecked(interface))
+				.await
+			{
+				properties.insert(interface.to_string(), values);
+			}
+		}
+		Ok(Self {
+			properties: properties
+				.into_iter()
+				.map(|(interface, values)| {
+					let values = values
+						.into_iter()
+						.map(|(name, value)| (name, MetadataValue::from(value)))
+						.collect();
+					(interface, values)
+				})
+				.collect(),
+		})
+	}
+
+	/// The value of a property, if the snapshot has it.
+	pub fn get(&self, interface: &str, property: &str) -> Option<&MetadataValue> {
+		self.properties.get(interface)?.get(property)
+	}
+
+	/// The properties that changed from this snapshot to `newer`.
+	///
+	/// `Position` is left out, as it changes continuously during playback
+	/// and the spec doesn't consider it a property change.
+	pub fn diff(&self, newer: &Self) -> SnapshotDiff {
+		let empty = BTreeMap::new();
+		let mut changes = Vec::new();
+		let interfaces = self
+			.properties
+			.keys()
+			.chain(newer.properties.keys())
+			.collect::<BTreeSet<_>>();
+		for interface in interfaces {
+			let old = self.properties.get(interface).unwrap_or(&empty);
+			let new = newer.properties.get(interface).unwrap_or(&empty);
+			let properties = old.keys().chain(new.keys()).collect::<BTreeSet<_>>();
+			for property in properties {
+				if property == "Position" && interface == "org.mpris.MediaPlayer2.Player" {
+					continue;
+				}
+				let (old, new) = (old.get(property), new.get(property));
+				if old != new {
+					changes.push(PropertyChange {
+						interface: interface.clone(),
+						property: property.clone(),
+						old: old.cloned(),
+						new: new.cloned(),
+					});
+				}
+			}
+		}
+		SnapshotDiff { changes }
+	}
+
+	/// Encodes the snapshot as JSON, keeping the exact type of every value,
+	/// so [PlayerStateSnapshot::from_json] can restore it.
+	#[cfg(feature = "json")]
+	pub fn to_json(&self) -> String {
+		let properties = self
+			.properties
+			.iter()
+			.map(|(interface, values)| {
+				let values = values
+					.iter()
+					.map(|(name, value)| (name.clone(), encode(value)))
+					.collect::<serde_json::Map<_, _>>();
+				(interface.clone(), serde_json::Value::Object(values))
+			})
+			.collect::<serde_json::Map<_, _>>();
+		serde_json::Value::Object(properties).to_string()
+	}
+
+	/// Decodes a snapshot produced by [PlayerStateSnapshot::to_json].
+	#[cfg(feature = "json")]
+	pub fn from_json(json: &str) -> Result<Self> {
+		let value = serde_json::from_str::<serde_json::Value>(json).map_err(invalid)?;
+		let properties = value
+			.as_object()
+			.ok_or_else(|| invalid("expected an object"))?
+			.iter()
+			.map(|(interface, values)| {
+				let values = values
+					.as_object()
+					.ok_or_else(|| invalid("expected an object"))?
+					.iter()
+					.map(|(name, value)| Ok((name.clone(), decode(value)?)))
+					.collect::<Result<_>>()?;
+				Ok((interface.clone(), values))
+			})
+			.collect::<Result<_>>()?;
+		Ok(Self { properties })
+	}
+}
+
+impl SnapshotDiff {
+	/// Whether both snapshots were identical.
+	pub fn is_empty(&self) -> bool {
+		self.changes.is_empty()
+	}
+
+	/// The names of the changed properties, e.g. `["Metadata", "PlaybackStatus"]`.
+	pub fn properties(&self) -> Vec<&str> {
+		self.changes
+			.iter()
+			.map(|change| change.property.as_str())
+			.collect()
+	}
+
+	/// Whether `property` changed, on any interface.
+	pub fn contains(&self, property: &str) -> bool {
+		self.changes
+			.iter()
+			.any(|change| change.property == property)
+	}
+}
@@ -0,0 +1,89 @@
+// SPDX-License-Identifier: MPL-2.0
+use crate::{
+	bindings::playerctld::PlayerctldProxy,
+	error::{Error, Result},
+	player::Player,
+};
+use std::ops::Deref;
+use zbus::{fdo::DBusProxy, names::OwnedBusName, Connection};
+
+/// The bus name playerctld runs under.
+pub const PLAYERCTLD_NAME: &str = "org.mpris.MediaPlayer2.playerctld";
+
+/// A client for [playerctld](https://github.com/altdesktop/playerctl),
+/// which tracks the active player for the whole session.
+///
+/// When playerctld is running, applications should follow its notion of the active player
+/// rather than tracking it themselves.
+#[derive(Debug, Clone)]
+pub struct Playerctld {
+	proxy: PlayerctldProxy<'static>,
+}
+
+impl Playerctld {
+	/// Creates a client for the playerctld instance on the given connection.
+	pub async fn new(connection: &Connection) -> Result<Self> {
+		PlayerctldProxy::new(connection)
+			.await
+			.map(Self::from)
+			.map_err(Error::from)
+	}
+
+	/// Whether playerctld is running on the given connection.
+	pub async fn is_running(connection: &Connection) -> Result<bool> {
+		let dbus = DBusProxy::new(connection).await?;
+		let name = OwnedBusName::try_from(PLAYERCTLD_NAME).map_err(zbus::Error::from)?;
+		dbus.name_has_owner(name.into()).await.map_err(Error::from)
+	}
+
+	/// The bus names of the players playerctld knows about, most recently active first.
+	pub async fn player_names(&self) -> Result<Vec<OwnedBusName>> {
+		self.proxy
+			.player_names()
+			.await?
+			.into_iter()
+			.map(|name| OwnedBusName::try_from(name).map_err(|err| Error::Zbus(err.into())))
+			.collect()
+	}
+
+	/// The bus name of the active player, if there are any players.
+	pub async fn active(&self) -> Result<Option<OwnedBusName>> {
+		self.player_names()
+			.await
+			.map(|names| names.into_iter().next())
+	}
+
+	/// The active player, if there are any players.
+	pub async fn active_player(&self) -> Result<Option<Player>> {
+		match self.active().await? {
+			Some(name) => Player::new(self.proxy.connection(), name).await.map(Some),
+			None => Ok(None),
+		}
+	}
+
+	/// Makes the next player the active one, returning its bus name.
+	pub async fn shift(&self) -> Result<OwnedBusName> {
+		let name = self.proxy.shift().await?;
+		OwnedBusName::try_from(name).map_err(|err| Error::Zbus(err.into()))
+	}
+
+	/// Makes the previous player the active one, returning its bus name.
+	pub async fn unshift(&self) -> Result<OwnedBusName> {
+		let name = self.proxy.unshift().await?;
+		OwnedBusName::try_from(name).map_err(|err| Error::Zbus(err.into()))
+	}
+}
+
+impl Deref for Playerctld {
+	type Target = PlayerctldProxy<'static>;
+
+	fn deref(&self) -> &Self::Target {
+		&self.proxy
+	}
+}
+
+impl From<PlayerctldProxy<'static>> for Playerctld {
+	fn from(proxy: PlayerctldProxy<'static>) -> Self {
+		Self { proxy }
+	}
+}
@@ -2,14 +2,27 @@
 pub mod id;
 pub mod ordering;
 pub mod playlist;
+pub mod sort;
+pub mod synced;
 
+use self::{id::PlaylistId, ordering::PlaylistOrdering, playlist::Playlist};
 use crate::{
 	bindings::playlist::PlaylistsProxy,
+	check_mpris_name,
 	error::{Error, Result},
 };
-use std::ops::Deref;
+use futures_util::{
+	future, pin_mut,
+	stream::{self, BoxStream},
+	Stream, StreamExt,
+};
+use std::{ops::Deref, str::FromStr};
 use zbus::{names::OwnedBusName, Connection};
 
+/// How many playlists are fetched at a time when searching.
+const FIND_PAGE_SIZE: u32 = 64;
+
+#[derive(Debug, Clone)]
 pub struct Playlists {
 	proxy: PlaylistsProxy<'static>,
 }
@@ -18,12 +31,140 @@ impl Playlists {
 	/// Creates a new instance of the `org.mpris.MediaPlayer2.Playlists` interface.
 	pub async fn new(connection: &Connection, name: OwnedBusName) -> Result<Self> {
 		PlaylistsProxy::builder(connection)
-			.destination(name)?
+			.destination(check_mpris_name(name)?)?
 			.build()
 			.await
 			.map(Self::from)
 			.map_err(Error::from)
 	}
+
+	/// Starts playing the given playlist.
+	pub async fn activate(&self, playlist: &Playlist) -> Result<()> {
+		self.activate_id(playlist.id()).await
+	}
+
+	/// Starts playing the playlist with the given id.
+	pub async fn activate_id(&self, id: &PlaylistId) -> Result<()> {
+		self.proxy.activate_playlist(id).await.map_err(Error::from)
+	}
+
+	/// Returns the playlist that is currently active, or None if there isn't one.
+	pub async fn active_playlist(&self) -> Result<Option<Playlist>> {
+		let (valid, playlist) = self.proxy.active_playlist().await?;
+		Ok(valid.then_some(playlist))
+	}
+
+	/// Returns the number of playlists.
+	pub async fn count(&self) -> Result<u32> {
+		self.proxy.playlist_count().await.map_err(Error::from)
+	}
+
+	/// Returns the orderings the player supports in [Playlists::list].
+	///
+	/// Orderings which aren't in the spec are skipped.
+	pub async fn orderings(&self) -> Result<Vec<PlaylistOrdering>> {
+		Ok(self
+			.proxy
+			.orderings()
+			.await?
+			.iter()
+			.filter_map(|ordering| PlaylistOrdering::from_str(ordering).ok())
+			.collect())
+	}
+
+	/// Returns a stream over every playlist, fetched `page_size` at a time.
+	///
+	/// Pages are requested until `PlaylistCount` playlists were retrieved,
+	/// continuing from however many the player actually returned, so players which
+	/// cap the page size are handled. The stream ends after the first error.
+	pub async fn iter_all(
+		&self,
+		ordering: PlaylistOrdering,
+		reverse: bool,
+		page_size: u32,
+	) -> Result<impl Stream<Item = Result<Playlist>> + '_> {
+		let count = self.count().await?;
+		let page_size = page_size.max(1);
+		Ok(stream::unfold(0, move |start| async move {
+			if start >= count {
+				return None;
+			}
+			match self.list(start, page_size, ordering, reverse).await {
+				Ok(page) if page.is_empty() => None,
+				Ok(page) => {
+					let next = start.saturating_add(page.len() as u32);
+					Some((page.into_iter().map(Ok).collect::<Vec<_>>(), next))
+				}
+				Err(err) => Some((vec![Err(err)], count)),
+			}
+		})
+		.flat_map(stream::iter))
+	}
+
+	/// Returns the first playlist named exactly `name`.
+	pub async fn find_by_name(&self, name: &str) -> Result<Option<Playlist>> {
+		self.find(|playlist| playlist.name() == name).await
+	}
+
+	/// Returns the first playlist whose name matches `name`, ignoring case.
+	pub async fn find_by_name_ignore_case(&self, name: &str) -> Result<Option<Playlist>> {
+		let name = name.to_lowercase();
+		self.find(|playlist| playlist.name().to_lowercase() == name)
+			.await
+	}
+
+	/// Returns a stream of playlists whose name or icon changed.
+	pub async fn playlist_changes(&self) -> Result<BoxStream<'static, Playlist>> {
+		Ok(self
+			.proxy
+			.receive_playlist_changed()
+			.await?
+			.filter_map(|signal| future::ready(signal.args().ok().map(|args| args.playlist)))
+			.boxed())
+	}
+
+	/// Returns a stream that yields the active playlist every time it changes,
+	/// or None while there isn't one. The current value is yielded first.
+	pub async fn active_playlist_changes(&self) -> BoxStream<'static, Option<Playlist>> {
+		self.proxy
+			.receive_active_playlist_changed()
+			.await
+			.filter_map(|change| async move {
+				let (valid, playlist) = change.get().await.ok()?;
+				Some(valid.then_some(playlist))
+			})
+			.boxed()
+	}
+
+	/// Returns up to `count` playlists starting at `start`, sorted by `ordering`.
+	pub async fn list(
+		&self,
+		start: u32,
+		count: u32,
+		ordering: PlaylistOrdering,
+		reverse: bool,
+	) -> Result<Vec<Playlist>> {
+		self.proxy
+			.get_playlists(start, count, ordering, reverse)
+			.await
+			.map_err(Error::from)
+	}
+
+	/// Pages through the playlists alphabetically until one matches,
+	/// as every player has to support that ordering.
+	async fn find<F: Fn(&Playlist) -> bool>(&self, matches: F) -> Result<Option<Playlist>> {
+		let playlists = self
+			.iter_all(PlaylistOrdering::Alphabetical, false, FIND_PAGE_SIZE)
+			.await?;
+		pin_mut!(playlists);
+		while let Some(playlist) = playlists.next().await {
+			let playlist = playlist?;
+			if matches(&playlist) {
+				return Ok(Some(playlist));
+			}
+		}
+		Ok(None)
+	}
 }
 
 impl Deref for Playlists {
@@ -39,3 +180,9 @@ impl From<PlaylistsProxy<'static>> for Playlists {
 		Self { proxy }
 	}
 }
+
+impl From<Playlists> for PlaylistsProxy<'static> {
+	fn from(playlists: Playlists) -> Self {
+		playlists.proxy
+	}
+}
@@ -36,7 +36,7 @@ impl<'a> AsRef<ObjectPath<'a>> for PlaylistId {
 
 impl PartialOrd for PlaylistId {
 	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-		self.0.as_str().partial_cmp(other.0.as_str())
+		Some(self.cmp(other))
 	}
 }
 
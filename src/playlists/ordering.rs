@@ -56,13 +56,19 @@ impl FromStr for PlaylistOrdering {
 	fn from_str(s: &str) -> Result<Self> {
 		match s.to_lowercase().trim() {
 			"alphabetical" => Ok(Self::Alphabetical),
-			"created" => Ok(Self::CreationDate),
-			"modified" => Ok(Self::ModifiedDate),
-			"played" => Ok(Self::LastPlayDate),
-			"user" => Ok(Self::UserDefined),
+			"creationdate" | "created" => Ok(Self::CreationDate),
+			"modifieddate" | "modified" => Ok(Self::ModifiedDate),
+			"lastplaydate" | "played" => Ok(Self::LastPlayDate),
+			"userdefined" | "user" => Ok(Self::UserDefined),
 			_ => Err(Error::InvalidEnum {
 				got: s.to_string(),
-				expected: &["Alphabetical", "Created", "Modified", "Played", "User"],
+				expected: &[
+					"Alphabetical",
+					"CreationDate",
+					"ModifiedDate",
+					"LastPlayDate",
+					"UserDefined",
+				],
 			}),
 		}
 	}
@@ -75,10 +81,10 @@ impl Display for PlaylistOrdering {
 			"{}",
 			match self {
 				Self::Alphabetical => "Alphabetical",
-				Self::CreationDate => "Created",
-				Self::ModifiedDate => "Modified",
-				Self::LastPlayDate => "Played",
-				Self::UserDefined => "User",
+				Self::CreationDate => "CreationDate",
+				Self::ModifiedDate => "ModifiedDate",
+				Self::LastPlayDate => "LastPlayDate",
+				Self::UserDefined => "UserDefined",
 			}
 		)
 	}
@@ -118,3 +124,24 @@ impl Visitor<'_> for PlaylistOrderingVisitor {
 		PlaylistOrdering::from_str(s).map_err(de::Error::custom)
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn round_trips_spec_names() {
+		for ordering in [
+			PlaylistOrdering::Alphabetical,
+			PlaylistOrdering::CreationDate,
+			PlaylistOrdering::ModifiedDate,
+			PlaylistOrdering::LastPlayDate,
+			PlaylistOrdering::UserDefined,
+		] {
+			let name = ordering.to_string();
+			assert_eq!(PlaylistOrdering::from_str(&name).unwrap(), ordering);
+		}
+		assert_eq!(PlaylistOrdering::UserDefined.to_string(), "UserDefined");
+		assert!(PlaylistOrdering::from_str("Shuffled").is_err());
+	}
+}
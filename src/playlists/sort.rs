@@ -0,0 +1,29 @@
+// SPDX-License-Identifier: MPL-2.0
+use super::{ordering::PlaylistOrdering, playlist::Playlist};
+use crate::track_list::sort::natural_cmp;
+
+/// Re-sorts fetched playlists by `ordering`, for players that ignore the ordering passed to `GetPlaylists`.
+///
+/// Names are compared case-insensitively, with runs of digits compared by value.
+/// The interface doesn't expose playlist dates, so the date orderings can't be applied locally;
+/// in that case the playlists are left as they are and false is returned.
+/// [PlaylistOrdering::UserDefined] keeps the player's order, reversed if `reverse` is set.
+pub fn sort_playlists(
+	playlists: &mut [Playlist],
+	ordering: PlaylistOrdering,
+	reverse: bool,
+) -> bool {
+	match ordering {
+		PlaylistOrdering::Alphabetical => {
+			playlists.sort_by(|a, b| natural_cmp(a.name(), b.name()));
+		}
+		PlaylistOrdering::UserDefined => {}
+		PlaylistOrdering::CreationDate
+		| PlaylistOrdering::ModifiedDate
+		| PlaylistOrdering::LastPlayDate => return false,
+	}
+	if reverse {
+		playlists.reverse();
+	}
+	true
+}
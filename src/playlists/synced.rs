@@ -0,0 +1,108 @@
+// SPDX-License-Identifier: MPL-2.0
+use super::{ordering::PlaylistOrdering, playlist::Playlist, Playlists};
+use crate::error::Result;
+use futures_util::{
+	stream::{self, BoxStream},
+	StreamExt, TryStreamExt,
+};
+use std::fmt;
+
+/// How many playlists are fetched at a time when (re)loading.
+const PAGE_SIZE: u32 = 64;
+
+/// A change applied to a [SyncedPlaylists].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlaylistsChange {
+	/// The name or icon of the playlist at `index` changed.
+	Changed { index: usize, playlist: Playlist },
+	/// The number of playlists changed, so the whole list was fetched again.
+	Reloaded,
+}
+
+enum Signal {
+	Changed(Playlist),
+	Count(u32),
+}
+
+/// An in-memory mirror of all of a player's playlists,
+/// kept current by applying `PlaylistChanged` signals and `PlaylistCount` changes.
+pub struct SyncedPlaylists {
+	playlists: Playlists,
+	ordering: PlaylistOrdering,
+	list: Vec<Playlist>,
+	signals: BoxStream<'static, Signal>,
+}
+
+impl SyncedPlaylists {
+	/// Subscribes to the playlist signals, then fetches every playlist in the given ordering.
+	pub async fn new(playlists: Playlists, ordering: PlaylistOrdering) -> Result<Self> {
+		let changed = playlists.playlist_changes().await?.map(Signal::Changed);
+		let count = playlists
+			.receive_playlist_count_changed()
+			.await
+			.filter_map(|change| async move { change.get().await.ok().map(Signal::Count) });
+		let signals = stream::select(changed, count.boxed()).boxed();
+		let mut synced = Self {
+			playlists,
+			ordering,
+			list: Vec::new(),
+			signals,
+		};
+		synced.reload().await?;
+		Ok(synced)
+	}
+
+	/// The playlists interface being mirrored.
+	pub fn playlists(&self) -> &Playlists {
+		&self.playlists
+	}
+
+	/// All playlists, in the ordering given on creation.
+	pub fn list(&self) -> &[Playlist] {
+		&self.list
+	}
+
+	/// Waits for the next signal, applies it and returns the resulting change.
+	///
+	/// Returns None once the player goes away.
+	/// Signals that don't change the mirror, such as the initial count, are skipped.
+	pub async fn next_change(&mut self) -> Option<Result<PlaylistsChange>> {
+		while let Some(signal) = self.signals.next().await {
+			match signal {
+				Signal::Changed(playlist) => {
+					let index = match self.list.iter().position(|p| p.id() == playlist.id()) {
+						Some(index) => index,
+						None => continue,
+					};
+					self.list[index] = playlist.clone();
+					return Some(Ok(PlaylistsChange::Changed { index, playlist }));
+				}
+				Signal::Count(count) if count as usize == self.list.len() => continue,
+				Signal::Count(_) => {
+					return Some(self.reload().await.map(|_| PlaylistsChange::Reloaded));
+				}
+			}
+		}
+		None
+	}
+
+	async fn reload(&mut self) -> Result<()> {
+		self.list = self
+			.playlists
+			.iter_all(self.ordering, false, PAGE_SIZE)
+			.await?
+			.try_collect()
+			.await?;
+		Ok(())
+	}
+}
+
+impl fmt::Debug for SyncedPlaylists {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("SyncedPlaylists")
+			.field("playlists", &self.playlists)
+			.field("ordering", &self.ordering)
+			.field("list", &self.list)
+			.finish_non_exhaustive()
+	}
+}
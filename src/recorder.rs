@@ -0,0 +1,514 @@
+// SPDX-License-Identifier: MPL-2.0
+use crate::{
+	error::{Error, Result},
+	media_player::MediaPlayer,
+	metadata::{
+		json::{decode, encode, invalid},
+		Metadata, MetadataValue,
+	},
+	player::{LoopStatus, PlaybackStatus},
+	server::{MprisServer, PlayerHandler, MPRIS_PATH},
+};
+use async_io::Timer;
+use futures_util::{
+	future::{self, Either},
+	pin_mut, StreamExt,
+};
+use serde_json::{json, Map, Value as JsonValue};
+use std::{
+	collections::HashMap,
+	ops::Deref,
+	str::FromStr,
+	time::{Duration, Instant},
+};
+use zbus::{
+	fdo::{DBusProxy, PropertiesProxy},
+	names::InterfaceName,
+	zvariant::Structure,
+	Connection, MatchRule, MessageStream, MessageType,
+};
+
+/// The interfaces whose properties are captured when a recording starts.
+const INTERFACES: [&str; 4] = [
+	"org.mpris.MediaPlayer2",
+	"org.mpris.MediaPlayer2.Player",
+	"org.mpris.MediaPlayer2.TrackList",
+	"org.mpris.MediaPlayer2.Playlists",
+];
+
+/// A signal emitted by a recorded player.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecordedSignal {
+	/// `PropertiesChanged` for one of the player's interfaces.
+	PropertiesChanged {
+		interface: String,
+		changed: HashMap<String, MetadataValue>,
+		invalidated: Vec<String>,
+	},
+	/// Any other signal, such as `Seeked` or `TrackAdded`, with its arguments.
+	Signal {
+		interface: String,
+		member: String,
+		args: Vec<MetadataValue>,
+	},
+}
+
+/// A signal, and when it was emitted, relative to the start of the recording.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedEvent {
+	pub at: Duration,
+	pub signal: RecordedSignal,
+}
+
+/// The properties of a player when recording started, followed by every signal it emitted.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Recording {
+	/// The bus name of the recorded player.
+	pub player: String,
+	/// The properties of each interface the player implements, by interface name.
+	pub properties: HashMap<String, HashMap<String, MetadataValue>>,
+	/// The signals, oldest first.
+	pub events: Vec<RecordedEvent>,
+}
+
+impl Recording {
+	/// Encodes the recording as JSON, keeping the exact type of every value,
+	/// so it can be attached to bug reports and restored by [Recording::from_json].
+	pub fn to_json(&self) -> String {
+		let properties = self
+			.properties
+			.iter()
+			.map(|(interface, properties)| (interface.clone(), encode_map(properties)))
+			.collect::<Map<String, JsonValue>>();
+		let events = self
+			.events
+			.iter()
+			.map(|event| {
+				let at = event.at.as_nanos() as u64;
+				match &event.signal {
+					RecordedSignal::PropertiesChanged {
+						interface,
+						changed,
+						invalidated,
+					} => json!({
+						"at": at,
+						"interface": interface,
+						"member": "PropertiesChanged",
+						"changed": encode_map(changed),
+						"invalidated": invalidated,
+					}),
+					RecordedSignal::Signal {
+						interface,
+						member,
+						args,
+					} => json!({
+						"at": at,
+						"interface": interface,
+						"member": member,
+						"args": args.iter().map(encode).collect::<Vec<_>>(),
+					}),
+				}
+			})
+			.collect::<Vec<_>>();
+		json!({
+			"player": self.player,
+			"properties": properties,
+			"events": events,
+		})
+		.to_string()
+	}
+
+	/// Decodes a recording produced by [Recording::to_json].
+	pub fn from_json(json: &str) -> Result<Self> {
+		let value = serde_json::from_str::<JsonValue>(json).map_err(invalid)?;
+		let player = value
+			.get("player")
+			.and_then(JsonValue::as_str)
+			.ok_or_else(|| invalid("missing player"))?
+			.to_string();
+		let properties = value
+			.get("properties")
+			.and_then(JsonValue::as_object)
+			.ok_or_else(|| invalid("missing properties"))?
+			.iter()
+			.map(|(interface, properties)| Ok((interface.clone(), decode_map(properties)?)))
+			.collect::<Result<_>>()?;
+		let events = value
+			.get("events")
+			.and_then(JsonValue::as_array)
+			.ok_or_else(|| invalid("missing events"))?
+			.iter()
+			.map(decode_event)
+			.collect::<Result<_>>()?;
+		Ok(Self {
+			player,
+			properties,
+			events,
+		})
+	}
+}
+
+/// Records the properties and signals of a live player, to reproduce its behaviour
+/// offline with a [Replayer].
+pub struct Recorder {
+	recording: Recording,
+	start: Instant,
+	messages: MessageStream,
+}
+
+impl Recorder {
+	/// Subscribes to the player's signals, then captures its current properties.
+	pub async fn new(player: &MediaPlayer) -> Result<Self> {
+		let connection = player.connection();
+		let owner = DBusProxy::new(connection)
+			.await?
+			.get_name_owner(player.destination().to_owned())
+			.await?;
+		let rule = MatchRule::builder()
+			.msg_type(MessageType::Signal)
+			.sender(owner.clone())?
+			.path(MPRIS_PATH)?
+			.build();
+		let messages = MessageStream::for_match_rule(rule, connection, None).await?;
+		let start = Instant::now();
+		let proxy = PropertiesProxy::builder(connection)
+			.destination(owner)?
+			.path(MPRIS_PATH)?
+			.build()
+			.await?;
+		let mut properties = HashMap::new();
+		for interface in INTERFACES {
+			// Players only implement some of the optional interfaces.
+			if let Ok(values) = proxy
+				.get_all(InterfaceName::from_static_str_unchecked(interface))
+				.await
+			{
+				let values = values
+					.into_iter()
+					.map(|(name, value)| (name, MetadataValue::from(value)))
+					.collect();
+				properties.insert(interface.to_string(), values);
+			}
+		}
+		Ok(Self {
+			recording: Recording {
+				player: player.destination().to_string(),
+				properties,
+				events: Vec::new(),
+			},
+			start,
+			messages,
+		})
+	}
+
+	/// Waits for the player's next signal, and records it.
+	///
+	/// Returns None once the connection is closed.
+	pub async fn next_event(&mut self) -> Option<&RecordedEvent> {
+		while let Some(message) = self.messages.next().await {
+			let Some(signal) = message.ok().and_then(|message| decode_signal(&message)) else {
+				continue;
+			};
+			self.recording.events.push(RecordedEvent {
+				at: self.start.elapsed(),
+				signal,
+			});
+			return self.recording.events.last();
+		}
+		None
+	}
+
+	/// Records the player's signals until `duration` has passed since recording started.
+	pub async fn record_until(&mut self, duration: Duration) -> &Recording {
+		let deadline = self.start + duration;
+		loop {
+			let next = self.next_event();
+			pin_mut!(next);
+			match future::select(next, Timer::at(deadline)).await {
+				Either::Left((Some(_), _)) => {}
+				_ => break,
+			}
+		}
+		&self.recording
+	}
+
+	/// The recording so far.
+	pub fn recording(&self) -> &Recording {
+		&self.recording
+	}
+
+	/// Stops recording.
+	pub fn finish(self) -> Recording {
+		self.recording
+	}
+}
+
+/// Plays a [Recording] back as a player, so clients see the recorded player's properties
+/// change and its signals arrive through their usual streams.
+///
+/// Only the `org.mpris.MediaPlayer2` and `org.mpris.MediaPlayer2.Player` interfaces are
+/// exported; other signals are skipped. Requests made by clients are ignored.
+#[derive(Debug, Clone)]
+pub struct Replayer {
+	server: MprisServer,
+	recording: Recording,
+}
+
+struct Ignore;
+
+impl PlayerHandler for Ignore {}
+
+impl Replayer {
+	/// Exports a player on the connection, with the properties the recording started with.
+	///
+	/// Register a name with [MprisServer::register] for clients to find it.
+	pub async fn new(connection: &Connection, recording: Recording) -> Result<Self> {
+		let server = MprisServer::new(connection, "", Ignore).await?;
+		let replayer = Self { server, recording };
+		for (interface, properties) in &replayer.recording.properties {
+			for (name, value) in properties {
+				replayer
+					.set_property(interface, name, value.clone())
+					.await?;
+			}
+		}
+		Ok(replayer)
+	}
+
+	/// The recording being played back.
+	pub fn recording(&self) -> &Recording {
+		&self.recording
+	}
+
+	/// Plays back every event with its recorded timing, relative to now.
+	pub async fn replay(&self) -> Result<()> {
+		let start = Instant::now();
+		for event in &self.recording.events {
+			Timer::at(start + event.at).await;
+			self.apply(event).await?;
+		}
+		Ok(())
+	}
+
+	/// Plays back every event at once, ignoring the recorded timing.
+	pub async fn replay_instantly(&self) -> Result<()> {
+		for event in &self.recording.events {
+			self.apply(event).await?;
+		}
+		Ok(())
+	}
+
+	/// Plays back a single event, e.g. to step through a recording.
+	pub async fn apply(&self, event: &RecordedEvent) -> Result<()> {
+		match &event.signal {
+			RecordedSignal::PropertiesChanged {
+				interface, changed, ..
+			} => {
+				for (name, value) in changed {
+					self.set_property(interface, name, value.clone()).await?;
+				}
+				Ok(())
+			}
+			RecordedSignal::Signal {
+				interface,
+				member,
+				args,
+			} if interface == "org.mpris.MediaPlayer2.Player" && member == "Seeked" => {
+				let position = args
+					.first()
+					.cloned()
+					.ok_or_else(|| Error::MissingField("Seeked position".to_string()))?;
+				self.server
+					.seeked(time::Duration::microseconds(i64::try_from(position)?))
+					.await
+			}
+			RecordedSignal::Signal { .. } => Ok(()),
+		}
+	}
+
+	async fn set_property(&self, interface: &str, name: &str, value: MetadataValue) -> Result<()> {
+		let server = &self.server;
+		match (interface, name) {
+			("org.mpris.MediaPlayer2", "CanQuit") => server.set_can_quit(value.try_into()?).await,
+			("org.mpris.MediaPlayer2", "CanRaise") => server.set_can_raise(value.try_into()?).await,
+			("org.mpris.MediaPlayer2", "CanSetFullscreen") => {
+				server.set_can_set_fullscreen(value.try_into()?).await
+			}
+			("org.mpris.MediaPlayer2", "Fullscreen") => {
+				server.set_fullscreen(value.try_into()?).await
+			}
+			("org.mpris.MediaPlayer2", "HasTrackList") => {
+				server.set_has_track_list(value.try_into()?).await
+			}
+			("org.mpris.MediaPlayer2", "Identity") => {
+				server.set_identity(&String::try_from(value)?).await
+			}
+			("org.mpris.MediaPlayer2", "DesktopEntry") => {
+				server
+					.set_desktop_entry(Some(&String::try_from(value)?))
+					.await
+			}
+			("org.mpris.MediaPlayer2", "SupportedUriSchemes") => {
+				server.set_supported_uri_schemes(value.try_into()?).await
+			}
+			("org.mpris.MediaPlayer2", "SupportedMimeTypes") => {
+				server.set_supported_mime_types(value.try_into()?).await
+			}
+			("org.mpris.MediaPlayer2.Player", "PlaybackStatus") => {
+				let status = PlaybackStatus::from_str(&String::try_from(value)?)?;
+				server.set_playback_status(status).await
+			}
+			("org.mpris.MediaPlayer2.Player", "LoopStatus") => {
+				let status = LoopStatus::from_str(&String::try_from(value)?)?;
+				server.set_loop_status(status).await
+			}
+			("org.mpris.MediaPlayer2.Player", "Rate") => server.set_rate(value.try_into()?).await,
+			("org.mpris.MediaPlayer2.Player", "Shuffle") => {
+				server.set_shuffle(value.try_into()?).await
+			}
+			("org.mpris.MediaPlayer2.Player", "Metadata") => {
+				let metadata = HashMap::<String, MetadataValue>::try_from(value)?
+					.into_iter()
+					.collect::<Metadata>();
+				// Replay exactly what the player sent, even if it breaks the guidelines.
+				server.set_metadata_unchecked(metadata).await
+			}
+			("org.mpris.MediaPlayer2.Player", "Volume") => {
+				server.set_volume(value.try_into()?).await
+			}
+			("org.mpris.MediaPlayer2.Player", "Position") => {
+				let position = time::Duration::microseconds(value.try_into()?);
+				server.set_position(position).await
+			}
+			("org.mpris.MediaPlayer2.Player", "MinimumRate") => {
+				server.set_minimum_rate(value.try_into()?).await
+			}
+			("org.mpris.MediaPlayer2.Player", "MaximumRate") => {
+				server.set_maximum_rate(value.try_into()?).await
+			}
+			("org.mpris.MediaPlayer2.Player", "CanGoNext") => {
+				server.set_can_go_next(value.try_into()?).await
+			}
+			("org.mpris.MediaPlayer2.Player", "CanGoPrevious") => {
+				server.set_can_go_previous(value.try_into()?).await
+			}
+			("org.mpris.MediaPlayer2.Player", "CanPlay") => {
+				server.set_can_play(value.try_into()?).await
+			}
+			("org.mpris.MediaPlayer2.Player", "CanPause") => {
+				server.set_can_pause(value.try_into()?).await
+			}
+			("org.mpris.MediaPlayer2.Player", "CanSeek") => {
+				server.set_can_seek(value.try_into()?).await
+			}
+			("org.mpris.MediaPlayer2.Player", "CanControl") => {
+				server.set_can_control(value.try_into()?).await
+			}
+			_ => Ok(()),
+		}
+	}
+}
+
+impl Deref for Replayer {
+	type Target = MprisServer;
+
+	fn deref(&self) -> &Self::Target {
+		&self.server
+	}
+}
+
+fn decode_signal(message: &zbus::Message) -> Option<RecordedSignal> {
+	let header = message.header().ok()?;
+	let interface = header.interface().ok()??.to_string();
+	let member = header.member().ok()??.to_string();
+	if interface == "org.freedesktop.DBus.Properties" && member == "PropertiesChanged" {
+		let (interface, changed, invalidated) = message
+			.body::<(
+				String,
+				HashMap<String, zbus::zvariant::OwnedValue>,
+				Vec<String>,
+			)>()
+			.ok()?;
+		let changed = changed
+			.into_iter()
+			.map(|(name, value)| (name, MetadataValue::from(value)))
+			.collect();
+		return Some(RecordedSignal::PropertiesChanged {
+			interface,
+			changed,
+			invalidated,
+		});
+	}
+	let args = match message.body::<Structure>() {
+		Ok(body) => body.fields().iter().map(MetadataValue::from).collect(),
+		Err(_) => Vec::new(),
+	};
+	Some(RecordedSignal::Signal {
+		interface,
+		member,
+		args,
+	})
+}
+
+fn encode_map(map: &HashMap<String, MetadataValue>) -> JsonValue {
+	JsonValue::Object(
+		map.iter()
+			.map(|(key, value)| (key.clone(), encode(value)))
+			.collect(),
+	)
+}
+
+fn decode_map(value: &JsonValue) -> Result<HashMap<String, MetadataValue>> {
+	value
+		.as_object()
+		.ok_or_else(|| invalid("expected an object"))?
+		.iter()
+		.map(|(key, value)| Ok((key.clone(), decode(value)?)))
+		.collect()
+}
+
+fn decode_event(value: &JsonValue) -> Result<RecordedEvent> {
+	let field = |name: &str| {
+		value
+			.get(name)
+			.and_then(JsonValue::as_str)
+			.map(str::to_string)
+			.ok_or_else(|| invalid(format!("missing event {name}")))
+	};
+	let at = value
+		.get("at")
+		.and_then(JsonValue::as_u64)
+		.ok_or_else(|| invalid("missing event time"))?;
+	let (interface, member) = (field("interface")?, field("member")?);
+	let signal = match value.get("changed") {
+		Some(changed) => RecordedSignal::PropertiesChanged {
+			interface,
+			changed: decode_map(changed)?,
+			invalidated: value
+				.get("invalidated")
+				.and_then(JsonValue::as_array)
+				.map(|names| {
+					names
+						.iter()
+						.filter_map(JsonValue::as_str)
+						.map(str::to_string)
+						.collect()
+				})
+				.unwrap_or_default(),
+		},
+		None => RecordedSignal::Signal {
+			interface,
+			member,
+			args: value
+				.get("args")
+				.and_then(JsonValue::as_array)
+				.map(|args| args.iter().map(decode).collect::<Result<_>>())
+				.transpose()?
+				.unwrap_or_default(),
+		},
+	};
+	Ok(RecordedEvent {
+		at: Duration::from_nanos(at),
+		signal,
+	})
+}
@@ -0,0 +1,531 @@
+// SPDX-License-Identifier: MPL-2.0
+#[cfg(feature = "art")]
+pub mod art;
+pub mod clock;
+pub mod command;
+mod interface;
+mod name;
+
+use self::{
+	clock::PlaybackClock,
+	command::{Command, CommandSender},
+	interface::{PlayerInterface, RootInterface},
+	name::RegisteredName,
+};
+use crate::{
+	error::{Error, Result},
+	metadata::Metadata,
+	player::{LoopStatus, PlaybackStatus},
+	track::TrackId,
+};
+use async_channel::Receiver;
+use std::{
+	collections::HashMap,
+	sync::{Arc, Mutex, MutexGuard},
+};
+use time::Duration;
+use zbus::{
+	fdo::{Properties, RequestNameFlags},
+	names::{OwnedWellKnownName, WellKnownName},
+	Connection, Interface,
+};
+
+/// The object path MPRIS players are exported at.
+pub const MPRIS_PATH: &str = "/org/mpris/MediaPlayer2";
+
+/// Receives the requests clients make to an [MprisServer].
+///
+/// Requests the player has declared it can't handle, e.g. `Next` while `CanGoNext` is false,
+/// are dropped before reaching the handler, as the spec requires.
+/// Every method does nothing by default.
+pub trait PlayerHandler: Send + Sync + 'static {
+	fn raise(&self) {}
+
+	fn quit(&self) {}
+
+	fn next(&self) {}
+
+	fn previous(&self) {}
+
+	fn pause(&self) {}
+
+	fn play_pause(&self) {}
+
+	fn stop(&self) {}
+
+	fn play(&self) {}
+
+	/// Seeks forwards by `offset`, or backwards if it is negative.
+	fn seek(&self, _offset: Duration) {}
+
+	/// Seeks to `position` in the current track, which is `track`.
+	fn set_position(&self, _track: TrackId, _position: Duration) {}
+
+	fn open_uri(&self, _uri: String) {}
+
+	/// Called after a client set `LoopStatus`.
+	fn set_loop_status(&self, _status: LoopStatus) {}
+
+	/// Called after a client set `Rate`.
+	fn set_rate(&self, _rate: f64) {}
+
+	/// Called after a client set `Shuffle`.
+	fn set_shuffle(&self, _shuffle: bool) {}
+
+	/// Called after a client set `Volume`.
+	fn set_volume(&self, _volume: f64) {}
+
+	/// Called after a client set `Fullscreen`.
+	fn set_fullscreen(&self, _fullscreen: bool) {}
+}
+
+/// Exports a player's `org.mpris.MediaPlayer2` and `org.mpris.MediaPlayer2.Player`
+/// interfaces, passing clients' requests to a [PlayerHandler].
+///
+/// The player reports its state through the setters, which emit `PropertiesChanged`
+/// with the new value when it differs from the old one.
+///
+/// Clones share the bus name from [MprisServer::register], which is released
+/// once the last of them is dropped.
+#[derive(Debug, Clone)]
+pub struct MprisServer {
+	connection: Connection,
+	name: Arc<Mutex<Option<RegisteredName>>>,
+}
+
+impl MprisServer {
+	/// Exports the interfaces on the given connection, stopped and without a track.
+	///
+	/// All capabilities are false, except for `CanControl`.
+	pub async fn new<H: PlayerHandler>(
+		connection: &Connection,
+		identity: &str,
+		handler: H,
+	) -> Result<Self> {
+		let handler: Arc<dyn PlayerHandler> = Arc::new(handler);
+		let root = RootInterface {
+			handler: handler.clone(),
+			can_quit: false,
+			can_raise: false,
+			can_set_fullscreen: false,
+			fullscreen: None,
+			has_track_list: false,
+			identity: identity.to_string(),
+			desktop_entry: None,
+			supported_uri_schemes: Vec::new(),
+			supported_mime_types: Vec::new(),
+		};
+		let player = PlayerInterface {
+			handler,
+			playback_status: PlaybackStatus::Stopped,
+			loop_status: LoopStatus::None,
+			rate: 1.0,
+			shuffle: false,
+			metadata: Metadata::default(),
+			volume: 1.0,
+			position: Duration::ZERO,
+			clock: None,
+			minimum_rate: 1.0,
+			maximum_rate: 1.0,
+			can_go_next: false,
+			can_go_previous: false,
+			can_play: false,
+			can_pause: false,
+			can_seek: false,
+			can_control: true,
+		};
+		let object_server = connection.object_server();
+		object_server.at(MPRIS_PATH, root).await?;
+		object_server.at(MPRIS_PATH, player).await?;
+		Ok(Self {
+			connection: connection.clone(),
+			name: Arc::default(),
+		})
+	}
+
+	/// Exports the interfaces like [MprisServer::new], delivering clients' requests
+	/// as [Command]s over a channel instead of calling a handler,
+	/// for players whose core runs on its own event loop.
+	///
+	/// The receiver is also a `Stream` of commands.
+	pub async fn with_commands(
+		connection: &Connection,
+		identity: &str,
+	) -> Result<(Self, Receiver<Command>)> {
+		let (sender, receiver) = async_channel::unbounded();
+		let server = Self::new(connection, identity, CommandSender(sender)).await?;
+		Ok((server, receiver))
+	}
+
+	/// Requests the bus name `org.mpris.MediaPlayer2.<app_name>`, so clients can find the player,
+	/// and returns it.
+	///
+	/// If `instance` is true, or the name is already owned by another player,
+	/// `.instance<pid>` is appended to the name, as the spec suggests for players
+	/// which can run more than once. Any name registered before is released.
+	pub async fn register(&self, app_name: &str, instance: bool) -> Result<OwnedWellKnownName> {
+		let pid = std::process::id();
+		let mut candidates = vec![format!("org.mpris.MediaPlayer2.{app_name}.instance{pid}")];
+		if !instance {
+			candidates.insert(0, format!("org.mpris.MediaPlayer2.{app_name}"));
+		}
+		for candidate in candidates {
+			let name = WellKnownName::try_from(candidate).map_err(zbus::Error::from)?;
+			match self
+				.connection
+				.request_name_with_flags(&name, RequestNameFlags::DoNotQueue.into())
+				.await
+			{
+				Err(zbus::Error::NameTaken) => continue,
+				reply => reply?,
+			};
+			let name = OwnedWellKnownName::from(name);
+			let mut registered = self.registered_name();
+			// Replacing a registration releases its name, which must not be this one.
+			if registered
+				.as_ref()
+				.is_none_or(|registered| registered.name != name)
+			{
+				*registered = Some(RegisteredName::new(self.connection.clone(), name.clone()));
+			}
+			return Ok(name);
+		}
+		Err(Error::Zbus(zbus::Error::NameTaken))
+	}
+
+	/// The bus name requested with [MprisServer::register], if any.
+	pub fn name(&self) -> Option<OwnedWellKnownName> {
+		self.registered_name()
+			.as_ref()
+			.map(|registered| registered.name.clone())
+	}
+
+	/// The connection the interfaces are exported on.
+	pub fn connection(&self) -> &Connection {
+		&self.connection
+	}
+
+	/// The playback status last set by the player.
+	pub async fn playback_status(&self) -> Result<PlaybackStatus> {
+		Ok(self.player().await?.get().await.playback_status)
+	}
+
+	/// The loop status, as last set by the player or a client.
+	pub async fn loop_status(&self) -> Result<LoopStatus> {
+		Ok(self.player().await?.get().await.loop_status)
+	}
+
+	/// The playback rate, as last set by the player or a client.
+	pub async fn rate(&self) -> Result<f64> {
+		Ok(self.player().await?.get().await.rate)
+	}
+
+	/// The metadata of the current track.
+	pub async fn metadata(&self) -> Result<Metadata> {
+		Ok(self.player().await?.get().await.metadata.clone())
+	}
+
+	/// The position in the current track, as clients would read it.
+	pub async fn position(&self) -> Result<Duration> {
+		Ok(self.player().await?.get().await.current_position())
+	}
+
+	pub async fn set_identity(&self, identity: &str) -> Result<()> {
+		self.update("Identity", |root: &mut RootInterface| {
+			root.identity = identity.to_string();
+		})
+		.await
+	}
+
+	/// Sets the basename of the player's `.desktop` file, or unsets it.
+	pub async fn set_desktop_entry(&self, desktop_entry: Option<&str>) -> Result<()> {
+		self.update("DesktopEntry", |root: &mut RootInterface| {
+			root.desktop_entry = desktop_entry.map(str::to_string);
+		})
+		.await
+	}
+
+	pub async fn set_can_quit(&self, can_quit: bool) -> Result<()> {
+		self.update("CanQuit", |root: &mut RootInterface| {
+			root.can_quit = can_quit;
+		})
+		.await
+	}
+
+	pub async fn set_can_raise(&self, can_raise: bool) -> Result<()> {
+		self.update("CanRaise", |root: &mut RootInterface| {
+			root.can_raise = can_raise;
+		})
+		.await
+	}
+
+	/// Sets whether clients can make the player fullscreen, e.g. to false while no video is shown.
+	///
+	/// Requests to set `Fullscreen` are passed to [PlayerHandler::set_fullscreen],
+	/// and the new value is announced right away.
+	pub async fn set_can_set_fullscreen(&self, can_set_fullscreen: bool) -> Result<()> {
+		self.update("CanSetFullscreen", |root: &mut RootInterface| {
+			root.can_set_fullscreen = can_set_fullscreen;
+		})
+		.await
+	}
+
+	/// Sets whether the player is fullscreen, declaring that it supports fullscreen.
+	pub async fn set_fullscreen(&self, fullscreen: bool) -> Result<()> {
+		self.update("Fullscreen", |root: &mut RootInterface| {
+			root.fullscreen = Some(fullscreen);
+		})
+		.await
+	}
+
+	pub async fn set_has_track_list(&self, has_track_list: bool) -> Result<()> {
+		self.update("HasTrackList", |root: &mut RootInterface| {
+			root.has_track_list = has_track_list;
+		})
+		.await
+	}
+
+	pub async fn set_supported_uri_schemes(&self, schemes: Vec<String>) -> Result<()> {
+		self.update("SupportedUriSchemes", |root: &mut RootInterface| {
+			root.supported_uri_schemes = schemes;
+		})
+		.await
+	}
+
+	pub async fn set_supported_mime_types(&self, mime_types: Vec<String>) -> Result<()> {
+		self.update("SupportedMimeTypes", |root: &mut RootInterface| {
+			root.supported_mime_types = mime_types;
+		})
+		.await
+	}
+
+	pub async fn set_playback_status(&self, status: PlaybackStatus) -> Result<()> {
+		self.update("PlaybackStatus", |player: &mut PlayerInterface| {
+			player.set_clocked_status(status);
+		})
+		.await
+	}
+
+	pub async fn set_loop_status(&self, status: LoopStatus) -> Result<()> {
+		self.update("LoopStatus", |player: &mut PlayerInterface| {
+			player.loop_status = status;
+		})
+		.await
+	}
+
+	pub async fn set_rate(&self, rate: f64) -> Result<()> {
+		self.update("Rate", |player: &mut PlayerInterface| {
+			player.set_clocked_rate(rate);
+		})
+		.await
+	}
+
+	pub async fn set_shuffle(&self, shuffle: bool) -> Result<()> {
+		self.update("Shuffle", |player: &mut PlayerInterface| {
+			player.shuffle = shuffle;
+		})
+		.await
+	}
+
+	/// Sets the metadata of the current track, or clears it if it is empty.
+	///
+	/// Fails without publishing it if it violates the MPRIS metadata guidelines,
+	/// e.g. by lacking `mpris:trackid`, as found by [Metadata::validate].
+	pub async fn set_metadata(&self, metadata: Metadata) -> Result<()> {
+		if !metadata.is_empty() {
+			let violations = metadata.validate();
+			if !violations.is_empty() {
+				return Err(Error::InvalidMetadata(violations));
+			}
+		}
+		self.set_metadata_unchecked(metadata).await
+	}
+
+	/// Sets the metadata of the current track without validating it,
+	/// for players which have to pass on metadata they don't control.
+	pub async fn set_metadata_unchecked(&self, metadata: Metadata) -> Result<()> {
+		self.update("Metadata", |player: &mut PlayerInterface| {
+			if let Some(clock) = &mut player.clock {
+				clock.set_length(metadata.length());
+			}
+			player.metadata = metadata;
+		})
+		.await
+	}
+
+	pub async fn set_volume(&self, volume: f64) -> Result<()> {
+		self.update("Volume", |player: &mut PlayerInterface| {
+			player.volume = volume;
+		})
+		.await
+	}
+
+	/// Sets the position in the current track, which clients read when they need it,
+	/// without notifying them.
+	///
+	/// Use [MprisServer::seeked] instead when the position jumps.
+	pub async fn set_position(&self, position: Duration) -> Result<()> {
+		self.player()
+			.await?
+			.get_mut()
+			.await
+			.set_clocked_position(position);
+		Ok(())
+	}
+
+	/// Makes `Position` advance by itself at the playback rate while playing,
+	/// so the player only needs to report where playback started, jumps and status changes,
+	/// or stops it from doing so.
+	pub async fn set_auto_position(&self, enabled: bool) -> Result<()> {
+		let player = self.player().await?;
+		let mut player = player.get_mut().await;
+		if enabled {
+			if player.clock.is_none() {
+				let mut clock =
+					PlaybackClock::new(player.playback_status, player.rate, player.position);
+				clock.set_length(player.metadata.length());
+				player.clock = Some(clock);
+			}
+		} else if let Some(clock) = player.clock.take() {
+			player.position = clock.position();
+		}
+		Ok(())
+	}
+
+	/// Sets the position in the current track and emits `Seeked`,
+	/// for when it jumps rather than progressing with playback.
+	pub async fn seeked(&self, position: Duration) -> Result<()> {
+		let player = self.player().await?;
+		player.get_mut().await.set_clocked_position(position);
+		PlayerInterface::seeked(
+			player.signal_context(),
+			position.whole_microseconds() as i64,
+		)
+		.await?;
+		Ok(())
+	}
+
+	pub async fn set_minimum_rate(&self, rate: f64) -> Result<()> {
+		self.update("MinimumRate", |player: &mut PlayerInterface| {
+			player.minimum_rate = rate;
+		})
+		.await
+	}
+
+	pub async fn set_maximum_rate(&self, rate: f64) -> Result<()> {
+		self.update("MaximumRate", |player: &mut PlayerInterface| {
+			player.maximum_rate = rate;
+		})
+		.await
+	}
+
+	pub async fn set_can_go_next(&self, can_go_next: bool) -> Result<()> {
+		self.update("CanGoNext", |player: &mut PlayerInterface| {
+			player.can_go_next = can_go_next;
+		})
+		.await
+	}
+
+	pub async fn set_can_go_previous(&self, can_go_previous: bool) -> Result<()> {
+		self.update("CanGoPrevious", |player: &mut PlayerInterface| {
+			player.can_go_previous = can_go_previous;
+		})
+		.await
+	}
+
+	pub async fn set_can_play(&self, can_play: bool) -> Result<()> {
+		self.update("CanPlay", |player: &mut PlayerInterface| {
+			player.can_play = can_play;
+		})
+		.await
+	}
+
+	pub async fn set_can_pause(&self, can_pause: bool) -> Result<()> {
+		self.update("CanPause", |player: &mut PlayerInterface| {
+			player.can_pause = can_pause;
+		})
+		.await
+	}
+
+	pub async fn set_can_seek(&self, can_seek: bool) -> Result<()> {
+		self.update("CanSeek", |player: &mut PlayerInterface| {
+			player.can_seek = can_seek;
+		})
+		.await
+	}
+
+	pub async fn set_can_control(&self, can_control: bool) -> Result<()> {
+		self.update("CanControl", |player: &mut PlayerInterface| {
+			player.can_control = can_control;
+		})
+		.await
+	}
+
+	/// Takes the player off the bus in an order which never shows clients a half-dead player:
+	/// it is stopped and its track cleared, then its bus name released,
+	/// and only then are its interfaces unexported.
+	///
+	/// This affects all clones of the server.
+	pub async fn shutdown(self) -> Result<()> {
+		self.set_playback_status(PlaybackStatus::Stopped).await?;
+		self.set_metadata(Metadata::default()).await?;
+		let registered = self.registered_name().take();
+		if let Some(registered) = registered {
+			registered.release().await?;
+		}
+		let object_server = self.connection.object_server();
+		object_server
+			.remove::<PlayerInterface, _>(MPRIS_PATH)
+			.await?;
+		object_server.remove::<RootInterface, _>(MPRIS_PATH).await?;
+		Ok(())
+	}
+
+	fn registered_name(&self) -> MutexGuard<'_, Option<RegisteredName>> {
+		self.name
+			.lock()
+			.unwrap_or_else(|poisoned| poisoned.into_inner())
+	}
+
+	async fn player(&self) -> Result<zbus::InterfaceRef<PlayerInterface>> {
+		Ok(self
+			.connection
+			.object_server()
+			.interface(MPRIS_PATH)
+			.await?)
+	}
+
+	/// Applies `update` to the exported interface, then emits `PropertiesChanged`
+	/// with the value of `property` as clients would read it, if it changed.
+	///
+	/// A property which can no longer be read is announced as invalidated.
+	async fn update<I, F>(&self, property: &'static str, update: F) -> Result<()>
+	where
+		I: Interface,
+		F: FnOnce(&mut I),
+	{
+		let interface = self
+			.connection
+			.object_server()
+			.interface::<_, I>(MPRIS_PATH)
+			.await?;
+		let mut exported = interface.get_mut().await;
+		let before = exported.get(property).await;
+		update(&mut exported);
+		let after = exported.get(property).await;
+		let ctxt = interface.signal_context();
+		match (before, after) {
+			(Some(Ok(before)), Some(Ok(after))) if before == after => {}
+			(_, Some(Ok(after))) => {
+				let changed = HashMap::from([(property, &*after)]);
+				Properties::properties_changed(ctxt, I::name(), &changed, &[]).await?;
+			}
+			(Some(Ok(_)), _) => {
+				Properties::properties_changed(ctxt, I::name(), &HashMap::new(), &[property])
+					.await?;
+			}
+			_ => {}
+		}
+		Ok(())
+	}
+}
@@ -0,0 +1,116 @@
+// SPDX-License-Identifier: MPL-2.0
+use crate::{
+	art::{fnv1a, sniff_mime_type},
+	error::Result,
+	metadata::path_to_file_url,
+};
+use base64::Engine;
+use std::{
+	fs,
+	path::{Path, PathBuf},
+};
+
+/// Turns cover art into URLs for `mpris:artUrl`, for players whose art isn't already
+/// a file or a URL clients can fetch.
+///
+/// Images are written to a directory of the publisher's own under `$XDG_RUNTIME_DIR`,
+/// named after their contents, so the same image always gets the same URL.
+/// Only the most recently published image is kept, and the directory is removed on drop.
+/// Where no file can be written, images are published as `data:` URIs instead.
+#[derive(Debug)]
+pub struct ArtPublisher {
+	dir: Option<PathBuf>,
+	current: Option<PathBuf>,
+}
+
+impl ArtPublisher {
+	/// Creates a publisher for the given application, whose files are kept apart
+	/// from other applications and instances.
+	pub fn new(app_name: &str) -> Self {
+		let dir = std::env::var_os("XDG_RUNTIME_DIR")
+			.filter(|dir| !dir.is_empty())
+			.map(|dir| {
+				PathBuf::from(dir)
+					.join("mpris-art")
+					.join(format!("{app_name}-{}", std::process::id()))
+			});
+		Self { dir, current: None }
+	}
+
+	/// Publishes the image, returning its URL.
+	///
+	/// The previously published image is removed, unless it is the same one.
+	pub fn publish(&mut self, data: &[u8]) -> String {
+		let mime_type = sniff_mime_type(data);
+		match self.write(data, mime_type) {
+			Ok(path) => {
+				if self
+					.current
+					.as_ref()
+					.is_some_and(|current| *current != path)
+				{
+					self.clear();
+				}
+				let url = path_to_file_url(&path);
+				self.current = Some(path);
+				url
+			}
+			Err(_) => {
+				self.clear();
+				let data = base64::engine::general_purpose::STANDARD.encode(data);
+				let mime_type = mime_type.unwrap_or("application/octet-stream");
+				format!("data:{mime_type};base64,{data}")
+			}
+		}
+	}
+
+	/// Publishes an image which is already on disk, returning its URL.
+	///
+	/// The file is used where it is, so it must outlive its use in the metadata.
+	pub fn publish_path<P: AsRef<Path>>(&mut self, path: P) -> Result<String> {
+		let path = fs::canonicalize(path)?;
+		self.clear();
+		Ok(path_to_file_url(&path))
+	}
+
+	/// Removes the most recently published image, e.g. when the track has no art.
+	pub fn clear(&mut self) {
+		if let Some(current) = self.current.take() {
+			let _ = fs::remove_file(current);
+		}
+	}
+
+	/// Writes the image under a name derived from its contents.
+	/// It is written to a temporary file first, so clients never read half an image.
+	fn write(&self, data: &[u8], mime_type: Option<&str>) -> Result<PathBuf> {
+		let dir = self.dir.as_ref().ok_or_else(|| {
+			std::io::Error::new(std::io::ErrorKind::NotFound, "XDG_RUNTIME_DIR is not set")
+		})?;
+		fs::create_dir_all(dir)?;
+		let extension = match mime_type {
+			Some("image/png") => "png",
+			Some("image/jpeg") => "jpg",
+			Some("image/gif") => "gif",
+			Some("image/webp") => "webp",
+			Some("image/bmp") => "bmp",
+			Some("image/x-icon") => "ico",
+			Some("image/svg+xml") => "svg",
+			_ => "img",
+		};
+		let path = dir.join(format!("{:016x}.{extension}", fnv1a(data)));
+		if !path.exists() {
+			let partial = path.with_extension("partial");
+			fs::write(&partial, data)?;
+			fs::rename(&partial, &path)?;
+		}
+		Ok(path)
+	}
+}
+
+impl Drop for ArtPublisher {
+	fn drop(&mut self) {
+		if let Some(dir) = &self.dir {
+			let _ = fs::remove_dir_all(dir);
+		}
+	}
+}
@@ -0,0 +1,119 @@
+// SPDX-License-Identifier: MPL-2.0
+use crate::player::PlaybackStatus;
+use std::time::Instant;
+use time::Duration;
+
+/// Extrapolates the position in the current track from the playback status and rate,
+/// so it doesn't have to be updated on every tick.
+///
+/// The position is measured from an origin on the monotonic clock,
+/// which is moved whenever the status, rate or position changes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlaybackClock {
+	status: PlaybackStatus,
+	rate: f64,
+	origin: Instant,
+	origin_position: Duration,
+	/// The length of the track, which the position doesn't advance past.
+	length: Option<Duration>,
+}
+
+impl PlaybackClock {
+	/// Creates a clock at `position`, advancing if `status` is playing.
+	pub fn new(status: PlaybackStatus, rate: f64, position: Duration) -> Self {
+		Self {
+			status,
+			rate,
+			origin: Instant::now(),
+			origin_position: position,
+			length: None,
+		}
+	}
+
+	/// The current position, which advances at the playback rate while playing.
+	pub fn position(&self) -> Duration {
+		self.position_at(Instant::now())
+	}
+
+	/// The position at the given instant, which must not be before the last change.
+	///
+	/// The position saturates instead of overflowing, and stops at the end of the track.
+	pub fn position_at(&self, now: Instant) -> Duration {
+		if self.status != PlaybackStatus::Playing {
+			return self.origin_position;
+		}
+		let elapsed =
+			Duration::try_from(now.saturating_duration_since(self.origin)).unwrap_or(Duration::MAX);
+		let advanced = Duration::saturating_seconds_f64(elapsed.as_seconds_f64() * self.rate);
+		let position = self.origin_position.saturating_add(advanced);
+		match self.length {
+			Some(length) if length > Duration::ZERO => position.min(length),
+			_ => position,
+		}
+	}
+
+	pub fn status(&self) -> PlaybackStatus {
+		self.status
+	}
+
+	pub fn rate(&self) -> f64 {
+		self.rate
+	}
+
+	pub fn length(&self) -> Option<Duration> {
+		self.length
+	}
+
+	pub fn set_status(&mut self, status: PlaybackStatus) {
+		self.rebase(self.position());
+		self.status = status;
+	}
+
+	pub fn set_rate(&mut self, rate: f64) {
+		self.rebase(self.position());
+		self.rate = rate;
+	}
+
+	/// Sets the length of the track, or None if it is unknown.
+	pub fn set_length(&mut self, length: Option<Duration>) {
+		self.length = length;
+	}
+
+	/// Jumps to `position`, e.g. after seeking or changing tracks.
+	pub fn set_position(&mut self, position: Duration) {
+		self.rebase(position);
+	}
+
+	fn rebase(&mut self, position: Duration) {
+		self.origin = Instant::now();
+		self.origin_position = position;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::time::Duration as StdDuration;
+
+	#[test]
+	fn advances_at_the_rate() {
+		let clock = PlaybackClock::new(PlaybackStatus::Playing, 2.0, Duration::seconds(1));
+		let now = clock.origin + StdDuration::from_secs(3);
+		assert_eq!(clock.position_at(now), Duration::seconds(7));
+	}
+
+	#[test]
+	fn saturates_instead_of_overflowing() {
+		let clock = PlaybackClock::new(PlaybackStatus::Playing, f64::MAX, Duration::MAX);
+		let now = clock.origin + StdDuration::from_secs(1);
+		assert_eq!(clock.position_at(now), Duration::MAX);
+	}
+
+	#[test]
+	fn stops_at_the_end_of_the_track() {
+		let mut clock = PlaybackClock::new(PlaybackStatus::Playing, 1e12, Duration::ZERO);
+		clock.set_length(Some(Duration::minutes(3)));
+		let now = clock.origin + StdDuration::from_secs(60);
+		assert_eq!(clock.position_at(now), Duration::minutes(3));
+	}
+}
@@ -0,0 +1,109 @@
+// SPDX-License-Identifier: MPL-2.0
+use super::PlayerHandler;
+use crate::{player::LoopStatus, track::TrackId};
+use async_channel::Sender;
+use time::Duration;
+
+/// A request made by a client, as delivered by [MprisServer::with_commands](super::MprisServer::with_commands).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+	Raise,
+	Quit,
+	Next,
+	Previous,
+	Pause,
+	PlayPause,
+	Stop,
+	Play,
+	/// Seek forwards by the offset, or backwards if it is negative.
+	Seek(Duration),
+	/// Seek to the position in the current track, which is the given track.
+	SetPosition(TrackId, Duration),
+	OpenUri(String),
+	/// A client set `LoopStatus`.
+	SetLoopStatus(LoopStatus),
+	/// A client set `Rate`.
+	SetRate(f64),
+	/// A client set `Shuffle`.
+	SetShuffle(bool),
+	/// A client set `Volume`.
+	SetVolume(f64),
+	/// A client set `Fullscreen`.
+	SetFullscreen(bool),
+}
+
+/// Forwards every request to a channel.
+pub(crate) struct CommandSender(pub(crate) Sender<Command>);
+
+impl CommandSender {
+	/// Commands sent after the receiver is dropped are discarded.
+	fn send(&self, command: Command) {
+		let _ = self.0.try_send(command);
+	}
+}
+
+impl PlayerHandler for CommandSender {
+	fn raise(&self) {
+		self.send(Command::Raise);
+	}
+
+	fn quit(&self) {
+		self.send(Command::Quit);
+	}
+
+	fn next(&self) {
+		self.send(Command::Next);
+	}
+
+	fn previous(&self) {
+		self.send(Command::Previous);
+	}
+
+	fn pause(&self) {
+		self.send(Command::Pause);
+	}
+
+	fn play_pause(&self) {
+		self.send(Command::PlayPause);
+	}
+
+	fn stop(&self) {
+		self.send(Command::Stop);
+	}
+
+	fn play(&self) {
+		self.send(Command::Play);
+	}
+
+	fn seek(&self, offset: Duration) {
+		self.send(Command::Seek(offset));
+	}
+
+	fn set_position(&self, track: TrackId, position: Duration) {
+		self.send(Command::SetPosition(track, position));
+	}
+
+	fn open_uri(&self, uri: String) {
+		self.send(Command::OpenUri(uri));
+	}
+
+	fn set_loop_status(&self, status: LoopStatus) {
+		self.send(Command::SetLoopStatus(status));
+	}
+
+	fn set_rate(&self, rate: f64) {
+		self.send(Command::SetRate(rate));
+	}
+
+	fn set_shuffle(&self, shuffle: bool) {
+		self.send(Command::SetShuffle(shuffle));
+	}
+
+	fn set_volume(&self, volume: f64) {
+		self.send(Command::SetVolume(volume));
+	}
+
+	fn set_fullscreen(&self, fullscreen: bool) {
+		self.send(Command::SetFullscreen(fullscreen));
+	}
+}
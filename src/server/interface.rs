@@ -0,0 +1,353 @@
+// SPDX-License-Identifier: MPL-2.0
+use super::{clock::PlaybackClock, PlayerHandler};
+use crate::{
+	metadata::Metadata,
+	player::{LoopStatus, PlaybackStatus},
+	track::TrackId,
+};
+use std::{collections::HashMap, str::FromStr, sync::Arc};
+use time::Duration;
+use zbus::{dbus_interface, fdo, zvariant::OwnedValue, SignalContext};
+
+/// The `org.mpris.MediaPlayer2` interface exported by an [MprisServer](super::MprisServer).
+pub(crate) struct RootInterface {
+	pub(crate) handler: Arc<dyn PlayerHandler>,
+	pub(crate) can_quit: bool,
+	pub(crate) can_raise: bool,
+	pub(crate) can_set_fullscreen: bool,
+	/// None if the player doesn't support fullscreen.
+	pub(crate) fullscreen: Option<bool>,
+	pub(crate) has_track_list: bool,
+	pub(crate) identity: String,
+	pub(crate) desktop_entry: Option<String>,
+	pub(crate) supported_uri_schemes: Vec<String>,
+	pub(crate) supported_mime_types: Vec<String>,
+}
+
+#[dbus_interface(name = "org.mpris.MediaPlayer2")]
+impl RootInterface {
+	fn raise(&self) {
+		if self.can_raise {
+			self.handler.raise();
+		}
+	}
+
+	fn quit(&self) {
+		if self.can_quit {
+			self.handler.quit();
+		}
+	}
+
+	#[dbus_interface(property)]
+	fn can_quit(&self) -> bool {
+		self.can_quit
+	}
+
+	#[dbus_interface(property)]
+	fn can_raise(&self) -> bool {
+		self.can_raise
+	}
+
+	#[dbus_interface(property)]
+	fn can_set_fullscreen(&self) -> bool {
+		self.can_set_fullscreen
+	}
+
+	#[dbus_interface(property)]
+	fn fullscreen(&self) -> fdo::Result<bool> {
+		self.fullscreen
+			.ok_or_else(|| fdo::Error::NotSupported("Fullscreen is not supported".to_string()))
+	}
+
+	#[dbus_interface(property)]
+	fn set_fullscreen(&mut self, value: bool) -> fdo::Result<()> {
+		if !self.can_set_fullscreen || self.fullscreen.is_none() {
+			return Err(fdo::Error::NotSupported(
+				"Fullscreen can't be set, as CanSetFullscreen is false".to_string(),
+			));
+		}
+		self.fullscreen = Some(value);
+		self.handler.set_fullscreen(value);
+		Ok(())
+	}
+
+	#[dbus_interface(property)]
+	fn has_track_list(&self) -> bool {
+		self.has_track_list
+	}
+
+	#[dbus_interface(property)]
+	fn identity(&self) -> String {
+		self.identity.clone()
+	}
+
+	#[dbus_interface(property)]
+	fn desktop_entry(&self) -> fdo::Result<String> {
+		self.desktop_entry
+			.clone()
+			.ok_or_else(|| fdo::Error::NotSupported("DesktopEntry is not set".to_string()))
+	}
+
+	#[dbus_interface(property)]
+	fn supported_uri_schemes(&self) -> Vec<String> {
+		self.supported_uri_schemes.clone()
+	}
+
+	#[dbus_interface(property)]
+	fn supported_mime_types(&self) -> Vec<String> {
+		self.supported_mime_types.clone()
+	}
+}
+
+/// The `org.mpris.MediaPlayer2.Player` interface exported by an
+/// [MprisServer](super::MprisServer).
+pub(crate) struct PlayerInterface {
+	pub(crate) handler: Arc<dyn PlayerHandler>,
+	pub(crate) playback_status: PlaybackStatus,
+	pub(crate) loop_status: LoopStatus,
+	pub(crate) rate: f64,
+	pub(crate) shuffle: bool,
+	pub(crate) metadata: Metadata,
+	pub(crate) volume: f64,
+	pub(crate) position: Duration,
+	/// Extrapolates the position, if enabled.
+	pub(crate) clock: Option<PlaybackClock>,
+	pub(crate) minimum_rate: f64,
+	pub(crate) maximum_rate: f64,
+	pub(crate) can_go_next: bool,
+	pub(crate) can_go_previous: bool,
+	pub(crate) can_play: bool,
+	pub(crate) can_pause: bool,
+	pub(crate) can_seek: bool,
+	pub(crate) can_control: bool,
+}
+
+impl PlayerInterface {
+	pub(crate) fn current_position(&self) -> Duration {
+		self.clock
+			.as_ref()
+			.map_or(self.position, PlaybackClock::position)
+	}
+
+	pub(crate) fn set_clocked_status(&mut self, status: PlaybackStatus) {
+		self.playback_status = status;
+		if let Some(clock) = &mut self.clock {
+			clock.set_status(status);
+		}
+	}
+
+	pub(crate) fn set_clocked_rate(&mut self, rate: f64) {
+		self.rate = rate;
+		if let Some(clock) = &mut self.clock {
+			clock.set_rate(rate);
+		}
+	}
+
+	pub(crate) fn set_clocked_position(&mut self, position: Duration) {
+		self.position = position;
+		if let Some(clock) = &mut self.clock {
+			clock.set_position(position);
+		}
+	}
+
+	fn check_control(&self, property: &str) -> fdo::Result<()> {
+		if self.can_control {
+			Ok(())
+		} else {
+			Err(fdo::Error::NotSupported(format!(
+				"{property} can't be set, as CanControl is false"
+			)))
+		}
+	}
+}
+
+#[dbus_interface(name = "org.mpris.MediaPlayer2.Player")]
+impl PlayerInterface {
+	fn next(&self) {
+		if self.can_go_next {
+			self.handler.next();
+		}
+	}
+
+	fn previous(&self) {
+		if self.can_go_previous {
+			self.handler.previous();
+		}
+	}
+
+	fn pause(&self) {
+		if self.can_pause {
+			self.handler.pause();
+		}
+	}
+
+	fn play_pause(&self) {
+		if self.can_pause {
+			self.handler.play_pause();
+		}
+	}
+
+	fn stop(&self) {
+		if self.can_control {
+			self.handler.stop();
+		}
+	}
+
+	fn play(&self) {
+		if self.can_play {
+			self.handler.play();
+		}
+	}
+
+	fn seek(&self, offset: i64) {
+		if self.can_seek {
+			self.handler.seek(Duration::microseconds(offset));
+		}
+	}
+
+	/// Ignored if `track_id` isn't the current track, or `position` is outside of it.
+	fn set_position(&self, track_id: TrackId, position: i64) {
+		if !self.can_seek || self.metadata.track().as_ref() != Some(&track_id) {
+			return;
+		}
+		let position = Duration::microseconds(position);
+		let beyond_end = self
+			.metadata
+			.length()
+			.is_some_and(|length| position > length);
+		if !position.is_negative() && !beyond_end {
+			self.handler.set_position(track_id, position);
+		}
+	}
+
+	fn open_uri(&self, uri: String) {
+		self.handler.open_uri(uri);
+	}
+
+	#[dbus_interface(signal)]
+	pub(crate) async fn seeked(ctxt: &SignalContext<'_>, position: i64) -> zbus::Result<()>;
+
+	#[dbus_interface(property)]
+	fn playback_status(&self) -> String {
+		self.playback_status.to_string()
+	}
+
+	#[dbus_interface(property)]
+	fn loop_status(&self) -> String {
+		self.loop_status.to_string()
+	}
+
+	#[dbus_interface(property)]
+	fn set_loop_status(&mut self, value: String) -> fdo::Result<()> {
+		self.check_control("LoopStatus")?;
+		let value =
+			LoopStatus::from_str(&value).map_err(|err| fdo::Error::InvalidArgs(err.to_string()))?;
+		self.loop_status = value;
+		self.handler.set_loop_status(value);
+		Ok(())
+	}
+
+	#[dbus_interface(property)]
+	fn rate(&self) -> f64 {
+		self.rate
+	}
+
+	/// Clamped to `MinimumRate` and `MaximumRate`. Setting it to 0 pauses instead, as the spec requires.
+	#[dbus_interface(property)]
+	fn set_rate(&mut self, value: f64) -> fdo::Result<()> {
+		self.check_control("Rate")?;
+		if value.is_nan() {
+			return Err(fdo::Error::InvalidArgs("Rate can't be NaN".to_string()));
+		}
+		if value == 0.0 {
+			self.pause();
+			return Ok(());
+		}
+		let value = value.clamp(self.minimum_rate, self.maximum_rate.max(self.minimum_rate));
+		self.set_clocked_rate(value);
+		self.handler.set_rate(value);
+		Ok(())
+	}
+
+	#[dbus_interface(property)]
+	fn shuffle(&self) -> bool {
+		self.shuffle
+	}
+
+	#[dbus_interface(property)]
+	fn set_shuffle(&mut self, value: bool) -> fdo::Result<()> {
+		self.check_control("Shuffle")?;
+		self.shuffle = value;
+		self.handler.set_shuffle(value);
+		Ok(())
+	}
+
+	#[dbus_interface(property)]
+	fn metadata(&self) -> HashMap<String, OwnedValue> {
+		self.metadata.clone().into()
+	}
+
+	#[dbus_interface(property)]
+	fn volume(&self) -> f64 {
+		self.volume
+	}
+
+	/// Negative volumes are raised to 0.
+	#[dbus_interface(property)]
+	fn set_volume(&mut self, value: f64) -> fdo::Result<()> {
+		self.check_control("Volume")?;
+		if value.is_nan() {
+			return Err(fdo::Error::InvalidArgs("Volume can't be NaN".to_string()));
+		}
+		let value = value.max(0.0);
+		self.volume = value;
+		self.handler.set_volume(value);
+		Ok(())
+	}
+
+	/// Changes are announced by the `Seeked` signal rather than `PropertiesChanged`.
+	#[dbus_interface(property)]
+	fn position(&self) -> i64 {
+		self.current_position().whole_microseconds() as i64
+	}
+
+	#[dbus_interface(property)]
+	fn minimum_rate(&self) -> f64 {
+		self.minimum_rate
+	}
+
+	#[dbus_interface(property)]
+	fn maximum_rate(&self) -> f64 {
+		self.maximum_rate
+	}
+
+	#[dbus_interface(property)]
+	fn can_go_next(&self) -> bool {
+		self.can_go_next
+	}
+
+	#[dbus_interface(property)]
+	fn can_go_previous(&self) -> bool {
+		self.can_go_previous
+	}
+
+	#[dbus_interface(property)]
+	fn can_play(&self) -> bool {
+		self.can_play
+	}
+
+	#[dbus_interface(property)]
+	fn can_pause(&self) -> bool {
+		self.can_pause
+	}
+
+	#[dbus_interface(property)]
+	fn can_seek(&self) -> bool {
+		self.can_seek
+	}
+
+	#[dbus_interface(property)]
+	fn can_control(&self) -> bool {
+		self.can_control
+	}
+}
@@ -0,0 +1,46 @@
+// SPDX-License-Identifier: MPL-2.0
+use zbus::{names::OwnedWellKnownName, Connection};
+
+/// A bus name owned by an [MprisServer](super::MprisServer), released when dropped.
+#[derive(Debug)]
+pub(crate) struct RegisteredName {
+	pub(crate) connection: Connection,
+	pub(crate) name: OwnedWellKnownName,
+	released: bool,
+}
+
+impl RegisteredName {
+	pub(crate) fn new(connection: Connection, name: OwnedWellKnownName) -> Self {
+		Self {
+			connection,
+			name,
+			released: false,
+		}
+	}
+
+	/// Releases the name right away, rather than when dropped.
+	pub(crate) async fn release(mut self) -> zbus::Result<bool> {
+		self.released = true;
+		self.connection.release_name(&self.name).await
+	}
+}
+
+impl Drop for RegisteredName {
+	/// Releases the name on the connection's executor, as dropping can't wait for it.
+	fn drop(&mut self) {
+		if self.released {
+			return;
+		}
+		let connection = self.connection.clone();
+		let name = self.name.clone();
+		self.connection
+			.executor()
+			.spawn(
+				async move {
+					let _ = connection.release_name(name).await;
+				},
+				"release MPRIS name",
+			)
+			.detach();
+	}
+}
@@ -1,4 +1,8 @@
 // SPDX-License-Identifier: MPL-2.0
+use crate::{
+	error::{Error, Result},
+	metadata::Metadata,
+};
 use serde::{Deserialize, Serialize};
 use std::{
 	cmp::Ordering,
@@ -7,11 +11,30 @@ use std::{
 };
 use zbus::zvariant::{ObjectPath, OwnedObjectPath, Type, Value};
 
+/// The track id used by the spec to indicate the absence of a track,
+/// e.g. to insert at the start of a track list.
+pub const NO_TRACK: &str = "/org/mpris/MediaPlayer2/TrackList/NoTrack";
+
 /// A reference to an MPRIS track.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Type, Serialize, Deserialize, Value)]
 pub struct TrackId(OwnedObjectPath);
 
 impl TrackId {
+	/// Returns the `NoTrack` sentinel id.
+	pub fn no_track() -> Self {
+		Self(ObjectPath::from_static_str_unchecked(NO_TRACK).into())
+	}
+
+	/// Whether this is the `NoTrack` sentinel id.
+	pub fn is_no_track(&self) -> bool {
+		self.0.as_str() == NO_TRACK
+	}
+
+	/// Extracts the track id from the `mpris:trackid` field of the given metadata.
+	pub fn from_metadata(metadata: &Metadata) -> Option<Self> {
+		metadata.track()
+	}
+
 	pub fn into_inner(self) -> OwnedObjectPath {
 		self.0
 	}
@@ -21,6 +44,22 @@ impl TrackId {
 	}
 }
 
+impl From<OwnedObjectPath> for TrackId {
+	fn from(path: OwnedObjectPath) -> Self {
+		Self(path)
+	}
+}
+
+impl TryFrom<&str> for TrackId {
+	type Error = Error;
+
+	fn try_from(path: &str) -> Result<Self> {
+		ObjectPath::try_from(path)
+			.map(|path| Self(path.into()))
+			.map_err(|err| Error::Zbus(zbus::Error::Variant(err)))
+	}
+}
+
 impl Deref for TrackId {
 	type Target = OwnedObjectPath;
 
@@ -37,7 +76,7 @@ impl<'a> AsRef<ObjectPath<'a>> for TrackId {
 
 impl PartialOrd for TrackId {
 	fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-		self.0.as_str().partial_cmp(other.0.as_str())
+		Some(self.cmp(other))
 	}
 }
 
@@ -1,12 +1,35 @@
 // SPDX-License-Identifier: MPL-2.0
+pub mod cache;
+pub mod diff;
+pub mod emulated;
+pub mod playlist_file;
+pub mod sort;
+pub mod synced;
+
 use crate::{
-	bindings::track_list::TrackListProxy,
+	bindings::{media_player::MediaPlayer2Proxy, player::PlayerProxy, track_list::TrackListProxy},
+	check_mpris_name,
 	error::{Error, Result},
 	metadata::Metadata,
+	player::Player,
 	track::TrackId,
 };
-use std::{collections::BTreeMap, ops::Deref};
-use zbus::{names::OwnedBusName, Connection};
+use futures_util::{
+	future,
+	stream::{self, BoxStream},
+	Stream, StreamExt,
+};
+use std::{
+	collections::{BTreeMap, HashMap},
+	ops::Deref,
+	path::Path,
+};
+use zbus::{
+	fdo,
+	names::OwnedBusName,
+	zvariant::{OwnedValue, Value},
+	Connection,
+};
 
 #[derive(Debug, Clone)]
 pub struct TrackList {
@@ -17,25 +40,80 @@ impl TrackList {
 	/// Creates a new instance of the `org.mpris.MediaPlayer2.TrackList` interface.
 	pub async fn new(connection: &Connection, name: OwnedBusName) -> Result<Self> {
 		TrackListProxy::builder(connection)
-			.destination(name)?
+			.destination(check_mpris_name(name)?)?
 			.build()
 			.await
 			.map(Self::from)
 			.map_err(Error::from)
 	}
 
+	/// Whether the track list can be edited.
+	pub async fn can_edit_tracks(&self) -> Result<bool> {
+		self.proxy.can_edit_tracks().await.map_err(Error::from)
+	}
+
 	/// Adds a new track to this track list.
-	pub async fn add_track<S: ToString>(
+	///
+	/// Returns false without calling the player if the track list can't be edited.
+	pub async fn add_track(
 		&self,
-		uri: S,
+		uri: &str,
 		after: &TrackId,
 		set_as_current: bool,
-	) -> Result<()> {
-		let uri = uri.to_string();
-		self.proxy
-			.add_track(&uri, after, set_as_current)
-			.await
-			.map_err(Error::from)
+	) -> Result<bool> {
+		if !self.can_edit_tracks().await? {
+			return Ok(false);
+		}
+		self.proxy.add_track(uri, after, set_as_current).await?;
+		Ok(true)
+	}
+
+	/// Adds a new track so that it ends up at `index`, or at the end if `index` is past it.
+	///
+	/// Returns false without calling the player if the track list can't be edited.
+	pub async fn insert_at(&self, index: usize, uri: &str, set_as_current: bool) -> Result<bool> {
+		let tracks = self.tracks().await?;
+		let after = match index.min(tracks.len()).checked_sub(1) {
+			Some(previous) => tracks[previous].clone(),
+			None => TrackId::no_track(),
+		};
+		self.add_track(uri, &after, set_as_current).await
+	}
+
+	/// Adds several tracks after `after`, keeping them in the given order.
+	///
+	/// The tracks are added back to front after the same track, so that no new ids need to be looked up.
+	/// Tracks the player rejects with an error of its own are skipped; returns how many were added,
+	/// or 0 without calling the player if the track list can't be edited.
+	/// Other errors, such as a lost connection or a timeout, are returned.
+	pub async fn add_tracks<I, S>(
+		&self,
+		uris: I,
+		after: &TrackId,
+		set_first_as_current: bool,
+	) -> Result<usize>
+	where
+		I: IntoIterator<Item = S>,
+		I::IntoIter: DoubleEndedIterator + ExactSizeIterator,
+		S: AsRef<str>,
+	{
+		if !self.can_edit_tracks().await? {
+			return Ok(0);
+		}
+		let mut added = 0;
+		for (index, uri) in uris.into_iter().enumerate().rev() {
+			let set_as_current = set_first_as_current && index == 0;
+			match self
+				.proxy
+				.add_track(uri.as_ref(), after, set_as_current)
+				.await
+			{
+				Ok(()) => added += 1,
+				Err(zbus::Error::FDO(err)) if is_rejection(&err) => {}
+				Err(err) => return Err(err.into()),
+			}
+		}
+		Ok(added)
 	}
 
 	/// Gets the metadata of the given tracks.
@@ -44,37 +122,272 @@ impl TrackList {
 		tracks: T,
 	) -> Result<Vec<Metadata>> {
 		self.proxy
-			.get_tracks_metadata(tracks.as_ref().to_vec())
+			.get_tracks_metadata(tracks.as_ref())
 			.await
 			.map(|x| x.into_iter().map(Metadata::from).collect())
 			.map_err(Error::from)
 	}
 
+	/// Gets the metadata of the given tracks in batches of `chunk_size`, with up to `concurrency`
+	/// `GetTracksMetadata` calls in flight, yielding each batch in order as it arrives.
+	///
+	/// This keeps messages small for huge track lists, and lets UIs show the first rows early.
+	pub fn get_tracks_metadata_chunked<'a>(
+		&'a self,
+		tracks: &'a [TrackId],
+		chunk_size: usize,
+		concurrency: usize,
+	) -> impl Stream<Item = Result<Vec<(TrackId, Metadata)>>> + 'a {
+		stream::iter(tracks.chunks(chunk_size.max(1)))
+			.map(move |chunk| async move {
+				let metadata = self.get_tracks_metadata(chunk).await?;
+				Ok(chunk.iter().cloned().zip(metadata).collect())
+			})
+			.buffered(concurrency.max(1))
+	}
+
+	/// Gets the raw metadata maps of the given tracks, to be read through
+	/// [MetadataRef](crate::metadata::borrowed::MetadataRef)
+	/// without converting every value.
+	pub async fn get_tracks_metadata_raw<T: AsRef<[TrackId]>>(
+		&self,
+		tracks: T,
+	) -> Result<Vec<HashMap<String, OwnedValue>>> {
+		self.proxy
+			.get_tracks_metadata(tracks.as_ref())
+			.await
+			.map_err(Error::from)
+	}
+
+	/// Removes every track from the track list.
+	///
+	/// Returns false without calling the player if the track list can't be edited.
+	pub async fn clear(&self) -> Result<bool> {
+		if !self.can_edit_tracks().await? {
+			return Ok(false);
+		}
+		for track in self.tracks().await? {
+			self.proxy.remove_track(&track).await?;
+		}
+		Ok(true)
+	}
+
+	/// Moves `track` to just after `after`, or to the start if `after` is [TrackId::no_track].
+	///
+	/// MPRIS has no native move, so this adds the track's `xesam:url` at the new spot,
+	/// which gives it a new id, and only then removes the original, so a failed add loses nothing.
+	/// Returns false without calling the player if the track list can't be edited.
+	pub async fn move_track(&self, track: &TrackId, after: &TrackId) -> Result<bool> {
+		if !self.can_edit_tracks().await? {
+			return Ok(false);
+		}
+		if track == after {
+			return Ok(true);
+		}
+		let url = self
+			.get_tracks_metadata([track.clone()])
+			.await?
+			.into_iter()
+			.next()
+			.and_then(|metadata| metadata.url())
+			.ok_or_else(|| Error::MissingField("xesam:url".to_string()))?;
+		self.proxy.add_track(&url, after, false).await?;
+		self.proxy.remove_track(track).await?;
+		Ok(true)
+	}
+
 	/// Goes to the specified track.
 	pub async fn go_to(&self, track: &TrackId) -> Result<()> {
 		self.proxy.go_to(track).await.map_err(Error::from)
 	}
 
 	/// Removes the specified track.
-	pub async fn remove(&self, track: &TrackId) -> Result<()> {
-		self.proxy.remove_track(track).await.map_err(Error::from)
+	///
+	/// Returns false without calling the player if the track list can't be edited.
+	pub async fn remove(&self, track: &TrackId) -> Result<bool> {
+		if !self.can_edit_tracks().await? {
+			return Ok(false);
+		}
+		self.proxy.remove_track(track).await?;
+		Ok(true)
 	}
 
 	/// Returns a list of all available [Track]s.
 	pub async fn tracks(&self) -> Result<Vec<TrackId>> {
-		self.proxy
-			.tracks()
-			.await
-			.map(|x| x.into_iter().map(TrackId::from).collect())
-			.map_err(Error::from)
+		self.proxy.tracks().await.map_err(Error::from)
+	}
+
+	/// Returns the number of tracks in the track list.
+	pub async fn len(&self) -> Result<usize> {
+		let tracks = self
+			.proxy
+			.inner()
+			.get_property::<OwnedValue>("Tracks")
+			.await?;
+		match &*tracks {
+			Value::Array(tracks) => Ok(tracks.len()),
+			_ => Err(Error::IncorrectValue {
+				wanted: "Array",
+				actual: tracks,
+			}),
+		}
+	}
+
+	/// Whether the track list has no tracks.
+	pub async fn is_empty(&self) -> Result<bool> {
+		self.len().await.map(|len| len == 0)
+	}
+
+	/// Returns a stream over the tracks and their metadata in pages of `page_size`,
+	/// fetching each page's metadata only once the previous page has been consumed.
+	///
+	/// The track ids are fetched up front, so pages reflect the track list at the time of the call.
+	pub async fn pages(
+		&self,
+		page_size: usize,
+	) -> Result<impl Stream<Item = Result<Vec<(TrackId, Metadata)>>> + '_> {
+		let tracks = self.tracks().await?;
+		let page_size = page_size.max(1);
+		Ok(stream::unfold(
+			tracks.into_iter(),
+			move |mut tracks| async move {
+				let page = tracks.by_ref().take(page_size).collect::<Vec<_>>();
+				if page.is_empty() {
+					return None;
+				}
+				let page = match self.get_tracks_metadata(&page).await {
+					Ok(metadata) => Ok(page.into_iter().zip(metadata).collect()),
+					Err(err) => Err(err),
+				};
+				Some((page, tracks))
+			},
+		))
+	}
+
+	/// Returns the position of the given track in the track list.
+	pub async fn index_of(&self, track: &TrackId) -> Result<Option<usize>> {
+		Ok(self.tracks().await?.iter().position(|id| id == track))
 	}
 
-	/// Returns a list of all available [Track]s and their associated metadata,
-	/// in order.
+	/// Returns the track the player is currently playing,
+	/// or None if it isn't one of the tracks in the track list.
+	pub async fn current_track(&self) -> Result<Option<TrackId>> {
+		let current = match self.player().await?.metadata().await?.track() {
+			Some(current) => current,
+			None => return Ok(None),
+		};
+		Ok(self.index_of(&current).await?.map(|_| current))
+	}
+
+	/// Returns a stream that yields the current track every time it changes,
+	/// or None while there is no current track.
+	///
+	/// No single MPRIS signal reports this, so it is derived from the player's metadata
+	/// (which follows [TrackList::go_to]) and the current track announced by `TrackListReplaced`.
+	/// The current value is yielded first.
+	pub async fn receive_current_track_changed(
+		&self,
+	) -> Result<BoxStream<'static, Option<TrackId>>> {
+		let from_metadata =
+			self.player()
+				.await?
+				.receive_metadata_changed()
+				.await
+				.then(|change| async move {
+					change
+						.get()
+						.await
+						.ok()
+						.and_then(|metadata| Metadata::from(metadata).track())
+				});
+		let from_replaced = self
+			.proxy
+			.receive_track_list_replaced()
+			.await?
+			.filter_map(|signal| {
+				let current = signal.args().ok().map(|args| args.current_track);
+				future::ready(current.map(|current| Some(current).filter(|id| !id.is_no_track())))
+			});
+		let mut last = None;
+		Ok(stream::select(from_metadata.boxed(), from_replaced)
+			.filter_map(move |current| {
+				let changed = last.as_ref() != Some(&current);
+				if changed {
+					last = Some(current.clone());
+				}
+				future::ready(changed.then_some(current))
+			})
+			.boxed())
+	}
+
+	/// Returns all available [Track]s and their associated metadata, keyed by track.
 	pub async fn detailed_tracks(&self) -> Result<BTreeMap<TrackId, Metadata>> {
+		self.tracks_with_metadata()
+			.await
+			.map(|tracks| tracks.into_iter().collect())
+	}
+
+	/// Returns all available [Track]s and their associated metadata, in track list order.
+	///
+	/// The metadata is fetched with a single `GetTracksMetadata` call.
+	pub async fn tracks_with_metadata(&self) -> Result<Vec<(TrackId, Metadata)>> {
 		let tracks = self.tracks().await?;
 		let metadata = self.get_tracks_metadata(&tracks).await?;
-		Ok(tracks.into_iter().zip(metadata.into_iter()).collect())
+		Ok(tracks.into_iter().zip(metadata).collect())
+	}
+
+	/// Exports the track list as an extended M3U playlist, e.g. to save the current queue.
+	///
+	/// Tracks without an `xesam:url` are skipped.
+	pub async fn export_m3u(&self) -> Result<String> {
+		self.tracks_with_metadata()
+			.await
+			.map(|tracks| playlist_file::to_m3u(&tracks))
+	}
+
+	/// Exports the track list as a PLS playlist.
+	///
+	/// Tracks without an `xesam:url` are skipped.
+	pub async fn export_pls(&self) -> Result<String> {
+		self.tracks_with_metadata()
+			.await
+			.map(|tracks| playlist_file::to_pls(&tracks))
+	}
+
+	/// Adds the entries of an M3U or extended M3U playlist to the end of the track list,
+	/// e.g. to restore a queue saved with [TrackList::export_m3u].
+	///
+	/// Relative entries are resolved against `dir`, the absolute path of the directory
+	/// the playlist was read from, and skipped without one.
+	/// Entries whose URI scheme the player doesn't support are skipped;
+	/// returns how many tracks were added.
+	pub async fn import_m3u(&self, m3u: &str, dir: Option<&Path>) -> Result<usize> {
+		let schemes = MediaPlayer2Proxy::builder(self.proxy.connection())
+			.destination(self.proxy.destination().to_owned())?
+			.build()
+			.await?
+			.supported_uri_schemes()
+			.await?;
+		let uris = playlist_file::parse_m3u(m3u, dir)
+			.into_iter()
+			.filter(|uri| {
+				let scheme = uri.split(':').next().unwrap_or_default();
+				schemes
+					.iter()
+					.any(|supported| supported.eq_ignore_ascii_case(scheme))
+			})
+			.collect::<Vec<_>>();
+		let after = self.tracks().await?.pop().unwrap_or_else(TrackId::no_track);
+		self.add_tracks(uris, &after, false).await
+	}
+
+	async fn player(&self) -> Result<Player> {
+		PlayerProxy::builder(self.proxy.connection())
+			.destination(self.proxy.destination().to_owned())?
+			.build()
+			.await
+			.map(Player::from)
+			.map_err(Error::from)
 	}
 }
 
@@ -91,3 +404,28 @@ impl From<TrackListProxy<'static>> for TrackList {
 		Self { proxy }
 	}
 }
+
+/// Whether the player itself refused a call, e.g. with `InvalidArgs`,
+/// rather than the call failing to reach it or to return.
+fn is_rejection(err: &fdo::Error) -> bool {
+	!matches!(
+		err,
+		fdo::Error::ZBus(_)
+			| fdo::Error::NoMemory(_)
+			| fdo::Error::ServiceUnknown(_)
+			| fdo::Error::NameHasNoOwner(_)
+			| fdo::Error::NoReply(_)
+			| fdo::Error::IOError(_)
+			| fdo::Error::BadAddress(_)
+			| fdo::Error::LimitsExceeded(_)
+			| fdo::Error::AuthFailed(_)
+			| fdo::Error::NoServer(_)
+			| fdo::Error::Timeout(_)
+			| fdo::Error::NoNetwork(_)
+			| fdo::Error::Disconnected(_)
+			| fdo::Error::UnknownMethod(_)
+			| fdo::Error::UnknownObject(_)
+			| fdo::Error::UnknownInterface(_)
+			| fdo::Error::TimedOut(_)
+	)
+}
@@ -0,0 +1,144 @@
+// SPDX-License-Identifier: MPL-2.0
+use super::TrackList;
+use crate::{error::Result, metadata::Metadata, track::TrackId};
+use futures_util::{stream, StreamExt};
+use std::{
+	collections::HashMap,
+	sync::{Arc, Mutex, MutexGuard},
+};
+
+/// Caches the metadata of the tracks in a [TrackList], so that rows which were
+/// already resolved don't cause another `GetTracksMetadata` call.
+///
+/// Clones share the same entries, so [MetadataCache::watch] can run on one clone
+/// while another serves lookups.
+#[derive(Debug, Clone)]
+pub struct MetadataCache {
+	track_list: TrackList,
+	entries: Arc<Mutex<HashMap<TrackId, Metadata>>>,
+}
+
+enum Event {
+	Changed(TrackId, Metadata),
+	Removed(TrackId),
+	Replaced,
+}
+
+impl MetadataCache {
+	/// Creates an empty cache for the given track list.
+	pub fn new(track_list: TrackList) -> Self {
+		Self {
+			track_list,
+			entries: Arc::default(),
+		}
+	}
+
+	/// The track list this cache belongs to.
+	pub fn track_list(&self) -> &TrackList {
+		&self.track_list
+	}
+
+	/// Returns the cached metadata of the given track, without calling the player.
+	pub fn cached(&self, track: &TrackId) -> Option<Metadata> {
+		self.entries().get(track).cloned()
+	}
+
+	/// Gets the metadata of the given track, fetching it if it isn't cached yet.
+	pub async fn get(&self, track: &TrackId) -> Result<Metadata> {
+		self.get_many(std::slice::from_ref(track))
+			.await
+			.map(|mut metadata| metadata.remove(0))
+	}
+
+	/// Gets the metadata of the given tracks, in order, fetching only the ones
+	/// which aren't cached yet with a single `GetTracksMetadata` call.
+	///
+	/// Tracks the player doesn't know get empty metadata.
+	pub async fn get_many<T: AsRef<[TrackId]>>(&self, tracks: T) -> Result<Vec<Metadata>> {
+		let tracks = tracks.as_ref();
+		let missing = {
+			let entries = self.entries();
+			tracks
+				.iter()
+				.filter(|track| !entries.contains_key(track))
+				.cloned()
+				.collect::<Vec<_>>()
+		};
+		if !missing.is_empty() {
+			let fetched = self.track_list.get_tracks_metadata(&missing).await?;
+			let mut entries = self.entries();
+			if fetched.len() == missing.len() {
+				// Key by the requested ids, as players may omit or rewrite `mpris:trackid`.
+				entries.extend(missing.into_iter().zip(fetched));
+			} else {
+				// The player skipped ids it doesn't know, so only the returned ids can tell them apart.
+				for metadata in fetched {
+					if let Some(track) = metadata.track().filter(|track| missing.contains(track)) {
+						entries.insert(track, metadata);
+					}
+				}
+			}
+		}
+		let entries = self.entries();
+		Ok(tracks
+			.iter()
+			.map(|track| entries.get(track).cloned().unwrap_or_default())
+			.collect())
+	}
+
+	/// Drops the cached metadata of the given track.
+	pub fn invalidate(&self, track: &TrackId) {
+		self.entries().remove(track);
+	}
+
+	/// Drops all cached metadata.
+	pub fn clear(&self) {
+		self.entries().clear();
+	}
+
+	/// Keeps the cache up to date with the track list's signals, until the player goes away.
+	///
+	/// `TrackMetadataChanged` replaces the cached entry, `TrackRemoved` drops it,
+	/// and `TrackListReplaced` clears the whole cache.
+	pub async fn watch(&self) -> Result<()> {
+		let changed = self
+			.track_list
+			.receive_track_metadata_changed()
+			.await?
+			.map(|signal| {
+				let args = signal.args().ok()?;
+				Some(Event::Changed(args.track_id, Metadata::from(args.metadata)))
+			});
+		let removed = self
+			.track_list
+			.receive_track_removed()
+			.await?
+			.map(|signal| {
+				let args = signal.args().ok()?;
+				Some(Event::Removed(args.track_id))
+			});
+		let replaced = self
+			.track_list
+			.receive_track_list_replaced()
+			.await?
+			.map(|_| Some(Event::Replaced));
+		let mut events = stream::select(stream::select(changed, removed), replaced);
+		while let Some(event) = events.next().await {
+			match event {
+				None => continue,
+				Some(Event::Changed(track, metadata)) => {
+					self.entries().insert(track, metadata);
+				}
+				Some(Event::Removed(track)) => self.invalidate(&track),
+				Some(Event::Replaced) => self.clear(),
+			}
+		}
+		Ok(())
+	}
+
+	fn entries(&self) -> MutexGuard<'_, HashMap<TrackId, Metadata>> {
+		self.entries
+			.lock()
+			.unwrap_or_else(|poisoned| poisoned.into_inner())
+	}
+}
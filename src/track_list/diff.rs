@@ -0,0 +1,155 @@
+// SPDX-License-Identifier: MPL-2.0
+use crate::{metadata::Metadata, track::TrackId};
+use std::collections::HashMap;
+
+/// A track which is in both snapshots of a [TrackListDiff], but at a different position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackMove {
+	pub track: TrackId,
+	/// The position in the older snapshot.
+	pub from: usize,
+	/// The position in the newer snapshot.
+	pub to: usize,
+}
+
+/// The differences between two snapshots of a track list,
+/// such as two results of [TrackList::tracks_with_metadata](super::TrackList::tracks_with_metadata).
+///
+/// Only the smallest set of tracks needed to go from one order to the other is reported as moved,
+/// so UIs can animate changes instead of re-rendering the whole list.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TrackListDiff {
+	/// Tracks only in the newer snapshot, with their position in it.
+	pub added: Vec<(usize, TrackId)>,
+	/// Tracks only in the older snapshot, with their position in it.
+	pub removed: Vec<(usize, TrackId)>,
+	/// Tracks which were reordered.
+	pub moved: Vec<TrackMove>,
+	/// Tracks in both snapshots whose metadata differs.
+	pub metadata_changed: Vec<TrackId>,
+}
+
+impl TrackListDiff {
+	/// Compares the `old` snapshot to the `new` one.
+	pub fn new(old: &[(TrackId, Metadata)], new: &[(TrackId, Metadata)]) -> Self {
+		let old_positions = positions(old);
+		let new_positions = positions(new);
+		let mut diff = Self::default();
+		for (index, (track, _)) in new.iter().enumerate() {
+			if !old_positions.contains_key(track) {
+				diff.added.push((index, track.clone()));
+			}
+		}
+		// Tracks in both snapshots, in old order, along with their new position.
+		let mut kept = Vec::new();
+		for (from, (track, metadata)) in old.iter().enumerate() {
+			match new_positions.get(track) {
+				Some(&to) => {
+					if new[to].1 != *metadata {
+						diff.metadata_changed.push(track.clone());
+					}
+					kept.push((track, from, to));
+				}
+				None => diff.removed.push((from, track.clone())),
+			}
+		}
+		let in_place = longest_increasing(&kept.iter().map(|(_, _, to)| *to).collect::<Vec<_>>());
+		diff.moved = kept
+			.into_iter()
+			.enumerate()
+			.filter(|(index, _)| !in_place[*index])
+			.map(|(_, (track, from, to))| TrackMove {
+				track: track.clone(),
+				from,
+				to,
+			})
+			.collect();
+		diff
+	}
+
+	/// Whether both snapshots were identical.
+	pub fn is_empty(&self) -> bool {
+		self.added.is_empty()
+			&& self.removed.is_empty()
+			&& self.moved.is_empty()
+			&& self.metadata_changed.is_empty()
+	}
+}
+
+fn positions(tracks: &[(TrackId, Metadata)]) -> HashMap<&TrackId, usize> {
+	tracks
+		.iter()
+		.enumerate()
+		.map(|(index, (track, _))| (track, index))
+		.collect()
+}
+
+/// Marks the elements of a longest strictly increasing subsequence of `values`.
+fn longest_increasing(values: &[usize]) -> Vec<bool> {
+	// `tails[len]` is the index of the smallest value ending an increasing run of length `len + 1`.
+	let mut tails: Vec<usize> = Vec::new();
+	let mut previous = vec![None; values.len()];
+	for (index, value) in values.iter().enumerate() {
+		let len = tails.partition_point(|&tail| values[tail] < *value);
+		if len > 0 {
+			previous[index] = Some(tails[len - 1]);
+		}
+		if len == tails.len() {
+			tails.push(index);
+		} else {
+			tails[len] = index;
+		}
+	}
+	let mut marked = vec![false; values.len()];
+	let mut next = tails.last().copied();
+	while let Some(index) = next {
+		marked[index] = true;
+		next = previous[index];
+	}
+	marked
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn snapshot(ids: &[u32]) -> Vec<(TrackId, Metadata)> {
+		ids.iter()
+			.map(|id| {
+				let path = format!("/org/mpris/MediaPlayer2/Track/{}", id);
+				(
+					TrackId::try_from(path.as_str()).unwrap(),
+					Metadata::default(),
+				)
+			})
+			.collect()
+	}
+
+	#[test]
+	fn marks_a_longest_increasing_run() {
+		assert_eq!(
+			longest_increasing(&[3, 0, 1, 4, 2]),
+			[false, true, true, false, true]
+		);
+		assert!(longest_increasing(&[]).is_empty());
+	}
+
+	#[test]
+	fn reports_the_fewest_moves() {
+		let old = snapshot(&[1, 2, 3, 4, 5]);
+		let new = snapshot(&[2, 3, 4, 1, 6]);
+		let diff = TrackListDiff::new(&old, &new);
+		assert_eq!(diff.added, [(4, new[4].0.clone())]);
+		assert_eq!(diff.removed, [(4, old[4].0.clone())]);
+		assert_eq!(
+			diff.moved,
+			[TrackMove {
+				track: old[0].0.clone(),
+				from: 0,
+				to: 3,
+			}]
+		);
+		assert!(diff.metadata_changed.is_empty());
+		assert!(TrackListDiff::new(&old, &old).is_empty());
+	}
+}
@@ -0,0 +1,110 @@
+// SPDX-License-Identifier: MPL-2.0
+use crate::{
+	error::{Error, Result},
+	player::{PlaybackStatus, Player},
+};
+use futures_util::StreamExt;
+use std::{
+	collections::VecDeque,
+	str::FromStr,
+	sync::{Arc, Mutex, MutexGuard},
+};
+
+/// A client-side queue of URIs for players that don't implement the TrackList interface,
+/// but support `OpenUri`.
+///
+/// Clones share the same queue, so [EmulatedQueue::watch] can run on one clone
+/// while another edits the queue.
+#[derive(Debug, Clone)]
+pub struct EmulatedQueue {
+	player: Player,
+	upcoming: Arc<Mutex<VecDeque<String>>>,
+}
+
+impl EmulatedQueue {
+	/// Creates an empty queue for the given player.
+	pub fn new(player: Player) -> Self {
+		Self {
+			player,
+			upcoming: Arc::default(),
+		}
+	}
+
+	/// The player the queue feeds.
+	pub fn player(&self) -> &Player {
+		&self.player
+	}
+
+	/// Returns the URIs which are yet to be played, in order.
+	pub fn upcoming(&self) -> Vec<String> {
+		self.queue().iter().cloned().collect()
+	}
+
+	/// Returns the number of URIs which are yet to be played.
+	pub fn len(&self) -> usize {
+		self.queue().len()
+	}
+
+	/// Whether there is nothing left to play.
+	pub fn is_empty(&self) -> bool {
+		self.queue().is_empty()
+	}
+
+	/// Adds a URI to the end of the queue.
+	pub fn add<S: Into<String>>(&self, uri: S) {
+		self.queue().push_back(uri.into());
+	}
+
+	/// Inserts a URI at `index`, or at the end if `index` is past it.
+	pub fn insert<S: Into<String>>(&self, index: usize, uri: S) {
+		let mut queue = self.queue();
+		let index = index.min(queue.len());
+		queue.insert(index, uri.into());
+	}
+
+	/// Removes the URI at `index`, returning it if there was one.
+	pub fn remove(&self, index: usize) -> Option<String> {
+		self.queue().remove(index)
+	}
+
+	/// Removes every upcoming URI.
+	pub fn clear(&self) {
+		self.queue().clear();
+	}
+
+	/// Opens the next URI in the queue, removing it.
+	///
+	/// Returns false if the queue was empty.
+	pub async fn next(&self) -> Result<bool> {
+		let uri = match self.queue().pop_front() {
+			Some(uri) => uri,
+			None => return Ok(false),
+		};
+		self.player.open_uri(&uri).await?;
+		Ok(true)
+	}
+
+	/// Opens the next URI every time the player stops after playing, until the player goes away.
+	///
+	/// Players that only support `OpenUri` stop once the opened track ends,
+	/// so a transition from playing to stopped is treated as the end of the track.
+	pub async fn watch(&self) -> Result<()> {
+		let mut changes = self.player.receive_playback_status_changed().await;
+		let mut was_playing = false;
+		while let Some(change) = changes.next().await {
+			let status = change.get().await.map_err(Error::from)?;
+			let status = PlaybackStatus::from_str(&status)?;
+			if was_playing && status == PlaybackStatus::Stopped {
+				self.next().await?;
+			}
+			was_playing = status == PlaybackStatus::Playing;
+		}
+		Ok(())
+	}
+
+	fn queue(&self) -> MutexGuard<'_, VecDeque<String>> {
+		self.upcoming
+			.lock()
+			.unwrap_or_else(|poisoned| poisoned.into_inner())
+	}
+}
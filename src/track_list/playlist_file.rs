@@ -0,0 +1,112 @@
+// SPDX-License-Identifier: MPL-2.0
+use crate::{
+	metadata::{path_to_file_url, Metadata},
+	track::TrackId,
+};
+use std::{fmt::Write, path::Path};
+
+/// Renders tracks as an extended M3U playlist.
+///
+/// Tracks without an `xesam:url` are skipped.
+pub fn to_m3u(tracks: &[(TrackId, Metadata)]) -> String {
+	let mut m3u = String::from("#EXTM3U\n");
+	for (_, metadata) in tracks {
+		let url = match metadata.url() {
+			Some(url) => url,
+			None => continue,
+		};
+		let _ = writeln!(
+			m3u,
+			"#EXTINF:{},{}",
+			seconds(metadata),
+			display_title(metadata)
+		);
+		let _ = writeln!(m3u, "{}", url);
+	}
+	m3u
+}
+
+/// Renders tracks as a PLS playlist.
+///
+/// Tracks without an `xesam:url` are skipped.
+pub fn to_pls(tracks: &[(TrackId, Metadata)]) -> String {
+	let mut pls = String::from("[playlist]\n");
+	let mut entries = 0;
+	for (_, metadata) in tracks {
+		let url = match metadata.url() {
+			Some(url) => url,
+			None => continue,
+		};
+		entries += 1;
+		let _ = writeln!(pls, "File{}={}", entries, url);
+		let _ = writeln!(pls, "Title{}={}", entries, display_title(metadata));
+		let _ = writeln!(pls, "Length{}={}", entries, seconds(metadata));
+	}
+	let _ = writeln!(pls, "NumberOfEntries={}", entries);
+	pls.push_str("Version=2\n");
+	pls
+}
+
+/// The length in whole seconds, or -1 if unknown, as both formats expect.
+fn seconds(metadata: &Metadata) -> i64 {
+	metadata
+		.length()
+		.map_or(-1, |length| length.whole_seconds())
+}
+
+/// `Artist - Title`, or whichever of the two is known, on a single line.
+fn display_title(metadata: &Metadata) -> String {
+	let artists = metadata.artists().map(|artists| artists.join(", "));
+	let title = match (artists, metadata.title()) {
+		(Some(artists), Some(title)) => format!("{} - {}", artists, title),
+		(Some(artists), None) => artists,
+		(None, Some(title)) => title,
+		(None, None) => String::new(),
+	};
+	title.replace(['\r', '\n'], " ")
+}
+
+/// Reads the entries of an M3U or extended M3U playlist, in order.
+///
+/// Absolute paths are turned into `file://` URLs, and URLs are kept as they are.
+/// Relative paths are resolved against `dir`, the absolute path of the directory
+/// the playlist is in, and skipped without one.
+pub fn parse_m3u(m3u: &str, dir: Option<&Path>) -> Vec<String> {
+	m3u.lines()
+		.map(|line| line.trim_start_matches('\u{feff}').trim())
+		.filter(|line| !line.is_empty() && !line.starts_with('#'))
+		.filter_map(|line| {
+			if line.contains("://") {
+				Some(line.to_string())
+			} else {
+				let path = match dir {
+					Some(dir) => dir.join(line),
+					None => Path::new(line).to_path_buf(),
+				};
+				path.is_absolute().then(|| path_to_file_url(&path))
+			}
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn parses_m3u_entries() {
+		let m3u = "\u{feff}#EXTM3U\n#EXTINF:200,A - B\nhttps://example.com/a.ogg\r\n\n/music/b c.flac\nsub/d.mp3\n";
+		assert_eq!(
+			parse_m3u(m3u, Some(Path::new("/playlists"))),
+			[
+				"https://example.com/a.ogg",
+				"file:///music/b%20c.flac",
+				"file:///playlists/sub/d.mp3",
+			]
+		);
+		assert_eq!(
+			parse_m3u(m3u, None),
+			["https://example.com/a.ogg", "file:///music/b%20c.flac"]
+		);
+	}
+}
@@ -0,0 +1,109 @@
+// SPDX-License-Identifier: MPL-2.0
+use crate::{metadata::Metadata, track::TrackId};
+use std::{cmp::Ordering, iter::Peekable, str::Chars};
+
+/// A metadata field to sort tracks by.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SortKey {
+	/// By title.
+	Title,
+	/// By the first artist, then in album order.
+	Artist,
+	/// By album, then disc number, then track number, then title.
+	Album,
+	/// By length, shortest first.
+	Length,
+}
+
+/// Sorts tracks by the given field, keeping tracks that compare equal in their current order.
+///
+/// Text is compared case-insensitively, with runs of digits compared by value
+/// (so "Track 2" comes before "Track 10"). Tracks missing the field sort last.
+pub fn sort_tracks(tracks: &mut [(TrackId, Metadata)], key: SortKey) {
+	tracks.sort_by(|(_, a), (_, b)| compare(a, b, key));
+}
+
+/// Compares two tracks' metadata by the given field, as [sort_tracks] does.
+pub fn compare(a: &Metadata, b: &Metadata, key: SortKey) -> Ordering {
+	match key {
+		SortKey::Title => compare_text(a.title(), b.title()),
+		SortKey::Artist => compare_text(first(a.artists()), first(b.artists()))
+			.then_with(|| compare(a, b, SortKey::Album)),
+		SortKey::Album => compare_text(a.album(), b.album())
+			.then_with(|| compare_option(a.disc_number(), b.disc_number()))
+			.then_with(|| compare_option(a.track_number(), b.track_number()))
+			.then_with(|| compare(a, b, SortKey::Title)),
+		SortKey::Length => compare_option(a.length(), b.length()),
+	}
+}
+
+fn first(values: Option<Vec<String>>) -> Option<String> {
+	values.and_then(|values| values.into_iter().next())
+}
+
+/// Orders present values before missing ones.
+fn compare_option<T: Ord>(a: Option<T>, b: Option<T>) -> Ordering {
+	match (a, b) {
+		(Some(a), Some(b)) => a.cmp(&b),
+		(Some(_), None) => Ordering::Less,
+		(None, Some(_)) => Ordering::Greater,
+		(None, None) => Ordering::Equal,
+	}
+}
+
+fn compare_text(a: Option<String>, b: Option<String>) -> Ordering {
+	match (a, b) {
+		(Some(a), Some(b)) => natural_cmp(&a, &b),
+		(a, b) => compare_option(a.map(|_| ()), b.map(|_| ())),
+	}
+}
+
+/// Compares strings case-insensitively, treating runs of digits as numbers.
+pub(crate) fn natural_cmp(a: &str, b: &str) -> Ordering {
+	let (mut a, mut b) = (a.chars().peekable(), b.chars().peekable());
+	loop {
+		let ordering = match (a.peek().copied(), b.peek().copied()) {
+			(None, None) => return Ordering::Equal,
+			(None, Some(_)) => return Ordering::Less,
+			(Some(_), None) => return Ordering::Greater,
+			(Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+				let (x, y) = (digits(&mut a), digits(&mut b));
+				let (x, y) = (x.trim_start_matches('0'), y.trim_start_matches('0'));
+				x.len().cmp(&y.len()).then_with(|| x.cmp(y))
+			}
+			(Some(x), Some(y)) => {
+				a.next();
+				b.next();
+				x.to_lowercase().cmp(y.to_lowercase())
+			}
+		};
+		if ordering != Ordering::Equal {
+			return ordering;
+		}
+	}
+}
+
+fn digits(chars: &mut Peekable<Chars<'_>>) -> String {
+	let mut digits = String::new();
+	while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+		digits.push(digit);
+	}
+	digits
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn compares_numbers_by_value() {
+		assert_eq!(natural_cmp("Track 2", "Track 10"), Ordering::Less);
+		assert_eq!(natural_cmp("track 02", "Track 2"), Ordering::Equal);
+		assert_eq!(natural_cmp("Track 2a", "Track 2B"), Ordering::Less);
+		assert_eq!(natural_cmp("Track", "Track 1"), Ordering::Less);
+		assert_eq!(
+			natural_cmp("99999999999999999999999", "100000000000000000000000"),
+			Ordering::Less
+		);
+	}
+}
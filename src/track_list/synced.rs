@@ -0,0 +1,166 @@
+// SPDX-License-Identifier: MPL-2.0
+use super::TrackList;
+use crate::{error::Result, metadata::Metadata, track::TrackId};
+use futures_util::{
+	stream::{self, BoxStream},
+	Stream, StreamExt,
+};
+use std::fmt;
+
+/// A change applied to a [SyncedTrackList].
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrackListChange {
+	/// A track was inserted at `index`.
+	Added { index: usize, track: TrackId },
+	/// The track that was at `index` was removed.
+	Removed { index: usize, track: TrackId },
+	/// The metadata of the track at `index` changed.
+	/// `track` is the new id, as players may change it along with the metadata.
+	MetadataChanged { index: usize, track: TrackId },
+	/// The whole list was replaced.
+	Replaced,
+}
+
+enum Signal {
+	Added(Metadata, TrackId),
+	Removed(TrackId),
+	MetadataChanged(TrackId, Metadata),
+	Replaced(Vec<TrackId>),
+}
+
+/// An in-memory mirror of a [TrackList] and the metadata of its tracks,
+/// kept current by applying the track list's signals.
+pub struct SyncedTrackList {
+	track_list: TrackList,
+	tracks: Vec<(TrackId, Metadata)>,
+	signals: BoxStream<'static, Signal>,
+}
+
+impl SyncedTrackList {
+	/// Subscribes to the track list's signals, then fetches its current contents.
+	pub async fn new(track_list: TrackList) -> Result<Self> {
+		let added = track_list.receive_track_added().await?.map(|signal| {
+			let args = signal.args().ok()?;
+			Some(Signal::Added(
+				Metadata::from(args.metadata),
+				args.after_track,
+			))
+		});
+		let removed = track_list.receive_track_removed().await?.map(|signal| {
+			let args = signal.args().ok()?;
+			Some(Signal::Removed(args.track_id))
+		});
+		let changed = track_list
+			.receive_track_metadata_changed()
+			.await?
+			.map(|signal| {
+				let args = signal.args().ok()?;
+				Some(Signal::MetadataChanged(
+					args.track_id,
+					Metadata::from(args.metadata),
+				))
+			});
+		let replaced = track_list
+			.receive_track_list_replaced()
+			.await?
+			.map(|signal| {
+				let args = signal.args().ok()?;
+				Some(Signal::Replaced(args.tracks))
+			});
+		let signals = stream::select_all([
+			added.boxed(),
+			removed.boxed(),
+			changed.boxed(),
+			replaced.boxed(),
+		])
+		.filter_map(|signal| async move { signal })
+		.boxed();
+		let tracks = track_list.tracks_with_metadata().await?;
+		Ok(Self {
+			track_list,
+			tracks,
+			signals,
+		})
+	}
+
+	/// The track list being mirrored.
+	pub fn track_list(&self) -> &TrackList {
+		&self.track_list
+	}
+
+	/// The tracks and their metadata, in order.
+	pub fn tracks(&self) -> &[(TrackId, Metadata)] {
+		&self.tracks
+	}
+
+	/// Returns the position of the given track.
+	pub fn position(&self, track: &TrackId) -> Option<usize> {
+		self.tracks.iter().position(|(id, _)| id == track)
+	}
+
+	/// Returns a stream that applies each signal as it arrives and yields the resulting change.
+	///
+	/// The stream ends once the player goes away. Signals about tracks that aren't in the list are skipped.
+	pub fn receive_changes(&mut self) -> impl Stream<Item = Result<TrackListChange>> + '_ {
+		stream::unfold(self, |synced| async move {
+			let change = synced.next_change().await?;
+			Some((change, synced))
+		})
+	}
+
+	/// Waits for the next signal, applies it and returns the resulting change.
+	///
+	/// Returns None once the player goes away. Signals about tracks that aren't in the list are skipped.
+	pub async fn next_change(&mut self) -> Option<Result<TrackListChange>> {
+		while let Some(signal) = self.signals.next().await {
+			match self.apply(signal).await {
+				Ok(Some(change)) => return Some(Ok(change)),
+				Ok(None) => continue,
+				Err(err) => return Some(Err(err)),
+			}
+		}
+		None
+	}
+
+	async fn apply(&mut self, signal: Signal) -> Result<Option<TrackListChange>> {
+		Ok(match signal {
+			Signal::Added(metadata, after) => {
+				let track = match metadata.track() {
+					Some(track) => track,
+					None => return Ok(None),
+				};
+				let index = match after.is_no_track() {
+					true => 0,
+					false => self
+						.position(&after)
+						.map_or(self.tracks.len(), |index| index + 1),
+				};
+				self.tracks.insert(index, (track.clone(), metadata));
+				Some(TrackListChange::Added { index, track })
+			}
+			Signal::Removed(track) => self.position(&track).map(|index| {
+				self.tracks.remove(index);
+				TrackListChange::Removed { index, track }
+			}),
+			Signal::MetadataChanged(track, metadata) => self.position(&track).map(|index| {
+				let track = metadata.track().unwrap_or(track);
+				self.tracks[index] = (track.clone(), metadata);
+				TrackListChange::MetadataChanged { index, track }
+			}),
+			Signal::Replaced(tracks) => {
+				let metadata = self.track_list.get_tracks_metadata(&tracks).await?;
+				self.tracks = tracks.into_iter().zip(metadata).collect();
+				Some(TrackListChange::Replaced)
+			}
+		})
+	}
+}
+
+impl fmt::Debug for SyncedTrackList {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("SyncedTrackList")
+			.field("track_list", &self.track_list)
+			.field("tracks", &self.tracks)
+			.finish_non_exhaustive()
+	}
+}
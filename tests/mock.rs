@@ -0,0 +1,254 @@
+// SPDX-License-Identifier: MPL-2.0
+#![cfg(feature = "mock")]
+
+use futures_util::StreamExt;
+use mpris2_zbus::{
+	compliance,
+	error::Error,
+	media_player::MediaPlayer,
+	metadata::Metadata,
+	mock::bus::TestBus,
+	player::{PlaybackStatus, Player},
+	server::{command::Command, MprisServer},
+};
+use std::{collections::HashMap, io};
+use time::Duration;
+use zbus::{
+	fdo::DBusProxy, names::OwnedBusName, zvariant::OwnedValue, MatchRule, MessageStream,
+	MessageType,
+};
+
+/// Starts a private bus, or returns None if `dbus-daemon` isn't installed.
+async fn test_bus() -> Option<TestBus> {
+	match TestBus::new().await {
+		Ok(bus) => Some(bus),
+		Err(Error::Io(err)) if err.kind() == io::ErrorKind::NotFound => {
+			eprintln!("skipping: dbus-daemon is not installed");
+			None
+		}
+		Err(err) => panic!("failed to start dbus-daemon: {}", err),
+	}
+}
+
+fn track(id: u32, seconds: i64) -> Metadata {
+	Metadata::builder()
+		.insert(
+			"mpris:trackid",
+			format!("/org/mpris/MediaPlayer2/Track/{}", id),
+		)
+		.title(format!("Track {}", id))
+		.length(Duration::seconds(seconds))
+		.build()
+}
+
+async fn client(bus: &TestBus, name: &impl AsRef<str>) -> Player {
+	let name = OwnedBusName::try_from(name.as_ref()).unwrap();
+	Player::new(bus.connection(), name).await.unwrap()
+}
+
+#[test]
+fn register_falls_back_to_an_instance_name() {
+	async_io::block_on(async {
+		let Some(bus) = test_bus().await else {
+			return;
+		};
+		let (first, _) = MprisServer::with_commands(&bus.connect().await.unwrap(), "Test")
+			.await
+			.unwrap();
+		let (second, _) = MprisServer::with_commands(&bus.connect().await.unwrap(), "Test")
+			.await
+			.unwrap();
+		let instance = format!("org.mpris.MediaPlayer2.test.instance{}", std::process::id());
+		assert_eq!(
+			first.register("test", false).await.unwrap().as_str(),
+			"org.mpris.MediaPlayer2.test"
+		);
+		assert_eq!(
+			second.register("test", false).await.unwrap().as_str(),
+			instance
+		);
+
+		let players = MediaPlayer::available_players(bus.connection())
+			.await
+			.unwrap();
+		assert!(players
+			.iter()
+			.any(|name| name.as_str() == "org.mpris.MediaPlayer2.test"));
+		assert!(players.iter().any(|name| name.as_str() == instance));
+	});
+}
+
+#[test]
+fn clamps_rate_and_volume() {
+	async_io::block_on(async {
+		let Some(bus) = test_bus().await else {
+			return;
+		};
+		let mock = bus.mock_player("clamps").await.unwrap();
+		mock.set_minimum_rate(0.5).await.unwrap();
+		mock.set_maximum_rate(2.0).await.unwrap();
+		let player = client(&bus, mock.name()).await;
+
+		player.set_rate(5.0).await.unwrap();
+		player.set_volume(-1.0).await.unwrap();
+		assert_eq!(
+			mock.wait_for_calls(2).await,
+			[Command::SetRate(2.0), Command::SetVolume(0.0)]
+		);
+		assert_eq!(mock.rate().await.unwrap(), 2.0);
+		assert!(player.set_rate(f64::NAN).await.is_err());
+	});
+}
+
+#[test]
+fn shutdown_stops_before_releasing_the_name() {
+	async_io::block_on(async {
+		let Some(bus) = test_bus().await else {
+			return;
+		};
+		let mock = bus.mock_player("shutdown").await.unwrap();
+		let name = mock.name().to_string();
+		mock.load(vec![track(1, 60)]).await.unwrap();
+		mock.set_playback_status(PlaybackStatus::Playing)
+			.await
+			.unwrap();
+		// Subscribe to the player's properties and the bus's name changes,
+		// then read both in the order they arrive.
+		let rule = MatchRule::builder()
+			.msg_type(MessageType::Signal)
+			.interface("org.freedesktop.DBus.Properties")
+			.unwrap()
+			.member("PropertiesChanged")
+			.unwrap()
+			.build();
+		let _properties = MessageStream::for_match_rule(rule, bus.connection(), None)
+			.await
+			.unwrap();
+		let _owners = DBusProxy::new(bus.connection())
+			.await
+			.unwrap()
+			.receive_name_owner_changed()
+			.await
+			.unwrap();
+		let mut messages = MessageStream::from(bus.connection());
+		MprisServer::clone(&mock).shutdown().await.unwrap();
+
+		let mut stopped = false;
+		while let Some(message) = messages.next().await {
+			let message = message.unwrap();
+			if message.message_type() != MessageType::Signal {
+				continue;
+			}
+			let member = message.member().unwrap();
+			if member.as_str() == "PropertiesChanged" {
+				let (_, changed, _) = message
+					.body::<(String, HashMap<String, OwnedValue>, Vec<String>)>()
+					.unwrap();
+				if let Some(status) = changed.get("PlaybackStatus") {
+					stopped = String::try_from(status.clone()).unwrap() == "Stopped";
+				}
+			} else if member.as_str() == "NameOwnerChanged" {
+				let (changed, _, new_owner) = message.body::<(String, String, String)>().unwrap();
+				if changed == name && new_owner.is_empty() {
+					break;
+				}
+			}
+		}
+		assert!(stopped, "the name was released before the player stopped");
+		let players = MediaPlayer::available_players(bus.connection())
+			.await
+			.unwrap();
+		assert!(!players.iter().any(|player| player.as_str() == name));
+	});
+}
+
+#[test]
+fn advance_moves_through_the_tracks() {
+	async_io::block_on(async {
+		let Some(bus) = test_bus().await else {
+			return;
+		};
+		let mock = bus.mock_player("advance").await.unwrap();
+		mock.load(vec![track(1, 10), track(2, 10)]).await.unwrap();
+		let player = client(&bus, mock.name()).await;
+		player.play().await.unwrap();
+		mock.wait_for_calls(1).await;
+		assert_eq!(mock.apply_calls().await.unwrap(), [Command::Play]);
+
+		mock.advance(Duration::seconds(15)).await.unwrap();
+		assert_eq!(mock.metadata().await.unwrap(), track(2, 10));
+		assert_eq!(mock.position().await.unwrap(), Duration::seconds(5));
+
+		mock.advance(Duration::seconds(10)).await.unwrap();
+		assert_eq!(
+			mock.playback_status().await.unwrap(),
+			PlaybackStatus::Stopped
+		);
+		assert_eq!(mock.position().await.unwrap(), Duration::ZERO);
+	});
+}
+
+#[test]
+fn mock_player_is_compliant() {
+	async_io::block_on(async {
+		let Some(bus) = test_bus().await else {
+			return;
+		};
+		let mock = bus.mock_player("compliant").await.unwrap();
+		mock.load(vec![track(1, 10)]).await.unwrap();
+		let name = OwnedBusName::try_from(mock.name().as_str()).unwrap();
+		let media_player = MediaPlayer::new(bus.connection(), name).await.unwrap();
+		let report = compliance::check(&media_player).await.unwrap();
+		assert!(report.is_compliant(), "{}", report);
+	});
+}
+
+#[cfg(feature = "recorder")]
+#[test]
+fn replayer_restores_recorded_properties() {
+	use mpris2_zbus::{
+		metadata::MetadataValue,
+		recorder::{RecordedEvent, RecordedSignal, Recording, Replayer},
+	};
+
+	async_io::block_on(async {
+		let Some(bus) = test_bus().await else {
+			return;
+		};
+		let status = |status: &str| {
+			HashMap::from([(
+				"PlaybackStatus".to_string(),
+				MetadataValue::Str(status.to_string()),
+			)])
+		};
+		let recording = Recording {
+			player: "org.mpris.MediaPlayer2.recorded".to_string(),
+			properties: HashMap::from([(
+				"org.mpris.MediaPlayer2.Player".to_string(),
+				status("Paused"),
+			)]),
+			events: vec![RecordedEvent {
+				at: std::time::Duration::ZERO,
+				signal: RecordedSignal::PropertiesChanged {
+					interface: "org.mpris.MediaPlayer2.Player".to_string(),
+					changed: status("Playing"),
+					invalidated: Vec::new(),
+				},
+			}],
+		};
+		let replayer = Replayer::new(&bus.connect().await.unwrap(), recording)
+			.await
+			.unwrap();
+		let name = replayer.register("replayed", false).await.unwrap();
+		let player = client(&bus, &name).await;
+		assert_eq!(
+			player.playback_status().await.unwrap(),
+			PlaybackStatus::Paused
+		);
+		replayer.replay_instantly().await.unwrap();
+		assert_eq!(
+			replayer.playback_status().await.unwrap(),
+			PlaybackStatus::Playing
+		);
+	});
+}